@@ -0,0 +1,131 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+
+pub const DISCOVERY_PORT: u16 = 47801;
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+const EXPIRE: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Deserialize)]
+struct Announcement {
+    instance: u32,
+    host_name: String,
+    player_name: String,
+    port: u16,
+}
+
+pub struct DiscoveredHost {
+    pub addr: SocketAddr,
+    pub host_name: String,
+    pub player_name: String,
+    last_seen: Instant,
+}
+
+/// Finds open hosts on the local network by UDP broadcast.
+pub struct LanDiscovery {
+    socket: Option<UdpSocket>,
+    last_announce: Option<Instant>,
+    pub hosts: Vec<DiscoveredHost>,
+    /// Waiting to join whichever host shows up first.
+    pub matching: bool,
+}
+
+impl LanDiscovery {
+    pub fn new() -> Self {
+        let socket = bind_shared(DISCOVERY_PORT);
+        if let Err(err) = &socket {
+            warn!("LAN discovery is disabled: {}", err);
+        }
+        Self {
+            socket: socket.ok(),
+            last_announce: None,
+            hosts: Vec::new(),
+            matching: false,
+        }
+    }
+
+    pub fn announce(&mut self, player_name: &str, port: u16) {
+        let socket = match &self.socket {
+            Some(socket) => socket,
+            None => return,
+        };
+        if matches!(self.last_announce, Some(last) if last.elapsed() < ANNOUNCE_INTERVAL) {
+            return;
+        }
+        self.last_announce = Some(Instant::now());
+        let announcement = Announcement {
+            instance: std::process::id(),
+            host_name: host_name().unwrap_or_else(|| player_name.into()),
+            player_name: player_name.into(),
+            port,
+        };
+        let _ = socket.send_to(
+            &serde_json::to_vec(&announcement).unwrap(),
+            (Ipv4Addr::BROADCAST, DISCOVERY_PORT),
+        );
+    }
+
+    pub fn poll(&mut self) {
+        let socket = match &self.socket {
+            Some(socket) => socket,
+            None => return,
+        };
+        let mut buf = [0; 1024];
+        while let Ok((len, addr)) = socket.recv_from(&mut buf) {
+            let announcement: Announcement = match serde_json::from_slice(&buf[..len]) {
+                Ok(announcement) => announcement,
+                Err(_) => continue,
+            };
+            if announcement.instance == std::process::id() {
+                continue;
+            }
+            let addr = SocketAddr::new(addr.ip(), announcement.port);
+            let host = DiscoveredHost {
+                addr,
+                host_name: announcement.host_name,
+                player_name: announcement.player_name,
+                last_seen: Instant::now(),
+            };
+            match self.hosts.iter_mut().find(|host| host.addr == addr) {
+                Some(known) => *known = host,
+                None => self.hosts.push(host),
+            }
+        }
+        self.hosts.retain(|host| host.last_seen.elapsed() < EXPIRE);
+    }
+}
+
+/// Binds a broadcast socket that other instances on this machine can bind too, so they all hear
+/// the announcements.
+fn bind_shared(port: u16) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.set_broadcast(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)).into())?;
+    Ok(socket.into())
+}
+
+#[cfg(unix)]
+fn host_name() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // gethostname writes at most `buf.len()` bytes.
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|byte| *byte == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned()).filter(|name| !name.is_empty())
+}
+
+/// Windows always sets it.
+#[cfg(not(unix))]
+fn host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
@@ -0,0 +1,441 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    io::ErrorKind,
+    marker::PhantomData,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::components::networking::{HugCommand, HugEvent, Payload};
+
+pub const DEFAULT_PORT: u16 = 47800;
+const RESEND_INTERVAL: Duration = Duration::from_millis(200);
+const HELLO_INTERVAL: Duration = Duration::from_millis(500);
+const TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+
+#[derive(Serialize, Deserialize)]
+pub enum Packet<T> {
+    Hello,
+    Bye,
+    Reliable { seq: u32, message: T },
+    Ack { seq: u32 },
+    Unreliable { seq: u32, message: T },
+}
+
+/// Unreliable messages of the same channel replace each other.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    HandControl,
+    Sync,
+}
+
+pub trait Message: Serialize + DeserializeOwned {
+    /// `None` for messages that must be delivered reliably and in order.
+    fn channel(&self) -> Option<Channel>;
+}
+
+impl Message for HugEvent {
+    fn channel(&self) -> Option<Channel> {
+        match self {
+            HugEvent::Push { payload } => payload_channel(payload),
+            _ => None,
+        }
+    }
+}
+
+impl Message for HugCommand {
+    fn channel(&self) -> Option<Channel> {
+        match self {
+            HugCommand::Push { payload } => payload_channel(payload),
+            _ => None,
+        }
+    }
+}
+
+fn payload_channel(payload: &Payload) -> Option<Channel> {
+    match payload {
+        Payload::HandControl { .. } => Some(Channel::HandControl),
+        Payload::Sync { .. } | Payload::State { .. } => Some(Channel::Sync),
+        _ => None,
+    }
+}
+
+/// Reliability layer for a single peer, sending `Out` and receiving `In`.
+///
+/// Reliable messages are delivered in order and resent until acknowledged.
+/// Unreliable messages are delivered at most once per poll, newest first wins.
+pub struct Connection<Out, In> {
+    pub addr: SocketAddr,
+    next_seq: u32,
+    unacked: VecDeque<(u32, Vec<u8>, Instant)>,
+    next_expected: u32,
+    pending: BTreeMap<u32, In>,
+    next_unreliable: u32,
+    latest: HashMap<Channel, u32>,
+    unreliable: HashMap<Channel, In>,
+    last_received: Instant,
+    data: PhantomData<Out>,
+}
+
+impl<Out: Message, In: Message> Connection<Out, In> {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            next_seq: 0,
+            unacked: VecDeque::new(),
+            next_expected: 0,
+            pending: BTreeMap::new(),
+            next_unreliable: 0,
+            latest: HashMap::new(),
+            unreliable: HashMap::new(),
+            last_received: Instant::now(),
+            data: Default::default(),
+        }
+    }
+
+    pub fn send(&mut self, socket: &UdpSocket, message: Out) {
+        let packet = if message.channel().is_some() {
+            self.next_unreliable += 1;
+            Packet::Unreliable {
+                seq: self.next_unreliable,
+                message,
+            }
+        } else {
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            Packet::Reliable { seq, message }
+        };
+        let bytes = serde_json::to_vec(&packet).unwrap();
+        let _ = socket.send_to(&bytes, self.addr);
+        if let Packet::Reliable { seq, .. } = packet {
+            self.unacked.push_back((seq, bytes, Instant::now()));
+        }
+    }
+
+    pub fn receive(&mut self, socket: &UdpSocket, packet: Packet<In>, messages: &mut Vec<In>) {
+        self.last_received = Instant::now();
+        match packet {
+            Packet::Reliable { seq, message } => {
+                send_packet(socket, self.addr, &Packet::<Out>::Ack { seq });
+                if seq >= self.next_expected {
+                    self.pending.insert(seq, message);
+                }
+                while let Some(message) = self.pending.remove(&self.next_expected) {
+                    messages.push(message);
+                    self.next_expected += 1;
+                }
+            }
+            Packet::Ack { seq } => {
+                self.unacked.retain(|(unacked, _, _)| *unacked != seq);
+            }
+            Packet::Unreliable { seq, message } => {
+                if let Some(channel) = message.channel() {
+                    let latest = self.latest.entry(channel).or_insert(0);
+                    if seq > *latest {
+                        *latest = seq;
+                        self.unreliable.insert(channel, message);
+                    }
+                }
+            }
+            Packet::Hello | Packet::Bye => {}
+        }
+    }
+
+    /// Takes the newest unreliable messages received since the last call.
+    pub fn drain_unreliable(&mut self, messages: &mut Vec<In>) {
+        messages.extend(self.unreliable.drain().map(|(_, message)| message));
+    }
+
+    pub fn resend(&mut self, socket: &UdpSocket) {
+        let now = Instant::now();
+        for (_, bytes, sent) in self.unacked.iter_mut() {
+            if now.duration_since(*sent) > RESEND_INTERVAL {
+                let _ = socket.send_to(bytes, self.addr);
+                *sent = now;
+            }
+        }
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.last_received.elapsed() > TIMEOUT
+    }
+}
+
+pub fn send_packet<T: Serialize>(socket: &UdpSocket, addr: SocketAddr, packet: &Packet<T>) {
+    let _ = socket.send_to(&serde_json::to_vec(packet).unwrap(), addr);
+}
+
+/// Reads every packet waiting on a non-blocking socket.
+pub fn recv_packets<T: DeserializeOwned>(socket: &UdpSocket) -> Vec<(SocketAddr, Packet<T>)> {
+    let mut packets = Vec::new();
+    let mut buf = vec![0; MAX_PACKET_SIZE];
+    loop {
+        match socket.recv_from(&mut buf) {
+            Ok((len, addr)) => match serde_json::from_slice(&buf[..len]) {
+                Ok(packet) => packets.push((addr, packet)),
+                Err(err) => warn!("invalid packet from {}: {}", addr, err),
+            },
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            // e.g. ICMP port unreachable on Windows; the peer may come back.
+            Err(err) if err.kind() == ErrorKind::ConnectionReset => continue,
+            Err(err) => {
+                warn!("failed to receive: {}", err);
+                break;
+            }
+        }
+    }
+    packets
+}
+
+pub fn bind(port: u16) -> std::io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(("0.0.0.0", port))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// The address other machines on the LAN can reach us at.
+pub fn local_ip() -> String {
+    // Connecting a UDP socket sends nothing, but picks the outgoing interface.
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.168.0.1:80")?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| "127.0.0.1".into())
+}
+
+/// Resolves `host` or `host:port`.
+pub fn resolve(key: &str) -> Option<SocketAddr> {
+    let addrs = if key.contains(':') {
+        key.to_socket_addrs()
+    } else {
+        (key, DEFAULT_PORT).to_socket_addrs()
+    };
+    addrs.ok().and_then(|mut addrs| addrs.next())
+}
+
+enum LanState {
+    Idle,
+    Hosting,
+    Joining {
+        connection: Connection<HugEvent, HugEvent>,
+        hello: Instant,
+    },
+    Connected {
+        connection: Connection<HugEvent, HugEvent>,
+    },
+    /// Talking to a dedicated room server instead of a peer.
+    Server {
+        connection: Connection<HugCommand, HugEvent>,
+        hello: Instant,
+    },
+}
+
+/// Peer-to-peer transport that speaks `HugCommand`/`HugEvent` like the hug server.
+/// The host plays the role of the server for the guest, unless a room server is given.
+pub struct LanTransport {
+    pub port: u16,
+    socket: Option<UdpSocket>,
+    state: LanState,
+    events: Vec<HugEvent>,
+}
+
+impl LanTransport {
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            socket: None,
+            state: LanState::Idle,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn with_server(mut self, key: &str) -> Self {
+        match (resolve(key), bind(0)) {
+            (Some(addr), Ok(socket)) => {
+                self.socket = Some(socket);
+                self.state = LanState::Server {
+                    connection: Connection::new(addr),
+                    hello: Instant::now(),
+                };
+            }
+            _ => error!("failed to reach the server {}", key),
+        }
+        self
+    }
+
+    pub fn has_server(&self) -> bool {
+        matches!(self.state, LanState::Server { .. })
+    }
+
+    pub fn is_hosting(&self) -> bool {
+        matches!(self.state, LanState::Hosting)
+    }
+
+    pub fn handle_command(&mut self, command: HugCommand) {
+        if let (Some(socket), LanState::Server { connection, .. }) = (&self.socket, &mut self.state)
+        {
+            connection.send(socket, command);
+            return;
+        }
+        match command {
+            HugCommand::CreateRoom => self.host(),
+            HugCommand::JoinRoom { key } => self.join(&key),
+            // Random matching needs a server or `LanDiscovery`.
+            HugCommand::JoinRandom => self.events.push(HugEvent::NotFound),
+            HugCommand::Leave => self.leave(),
+            HugCommand::Push { payload } => {
+                if let (Some(socket), LanState::Connected { connection }) =
+                    (&self.socket, &mut self.state)
+                {
+                    connection.send(socket, HugEvent::Push { payload });
+                }
+            }
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<HugEvent> {
+        let mut events = self.events.split_off(0);
+        let socket = match &self.socket {
+            Some(socket) => socket,
+            None => return events,
+        };
+        if let LanState::Server { connection, hello } = &mut self.state {
+            for (addr, packet) in recv_packets(socket) {
+                if addr == connection.addr {
+                    connection.receive(socket, packet, &mut events);
+                }
+            }
+            connection.drain_unreliable(&mut events);
+            connection.resend(socket);
+            // Keeps the server from timing us out while we wait for a partner.
+            if hello.elapsed() > HELLO_INTERVAL {
+                send_packet(socket, connection.addr, &Packet::<HugCommand>::Hello);
+                *hello = Instant::now();
+            }
+            return events;
+        }
+        for (addr, packet) in recv_packets(socket) {
+            let next = match &mut self.state {
+                LanState::Hosting => match packet {
+                    Packet::Hello => {
+                        info!("{} joined", addr);
+                        let mut connection = Connection::new(addr);
+                        connection.send(
+                            socket,
+                            HugEvent::Joined {
+                                is_primary: false,
+                                authoritative: false,
+                            },
+                        );
+                        events.push(HugEvent::Joined {
+                            is_primary: true,
+                            authoritative: false,
+                        });
+                        Some(LanState::Connected { connection })
+                    }
+                    _ => None,
+                },
+                LanState::Joining { connection, .. } if connection.addr == addr => {
+                    let mut connection = std::mem::replace(connection, Connection::new(addr));
+                    connection.receive(socket, packet, &mut events);
+                    Some(LanState::Connected { connection })
+                }
+                LanState::Connected { connection } if connection.addr == addr => match packet {
+                    Packet::Bye => {
+                        events.push(HugEvent::Left);
+                        Some(LanState::Idle)
+                    }
+                    packet => {
+                        connection.receive(socket, packet, &mut events);
+                        None
+                    }
+                },
+                _ => None,
+            };
+            if let Some(state) = next {
+                self.state = state;
+            }
+        }
+        let next = match &mut self.state {
+            LanState::Joining { connection, hello } => {
+                if hello.elapsed() > HELLO_INTERVAL {
+                    send_packet(socket, connection.addr, &Packet::<HugEvent>::Hello);
+                    *hello = Instant::now();
+                }
+                None
+            }
+            LanState::Connected { connection } => {
+                connection.drain_unreliable(&mut events);
+                connection.resend(socket);
+                if connection.is_timed_out() {
+                    warn!("{} timed out", connection.addr);
+                    events.push(HugEvent::Left);
+                    Some(LanState::Idle)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if let Some(state) = next {
+            self.state = state;
+        }
+        events
+    }
+
+    fn host(&mut self) {
+        match bind(self.port) {
+            Ok(socket) => {
+                self.socket = Some(socket);
+                self.state = LanState::Hosting;
+                let key = format!("{}:{}", local_ip(), self.port);
+                self.events.push(HugEvent::RoomCreated { key });
+            }
+            Err(err) => {
+                error!("failed to host on port {}: {}", self.port, err);
+                self.events.push(HugEvent::NotFound);
+            }
+        }
+    }
+
+    fn join(&mut self, key: &str) {
+        let addr = match resolve(key) {
+            Some(addr) => addr,
+            None => {
+                self.events.push(HugEvent::NotFound);
+                return;
+            }
+        };
+        match bind(0) {
+            Ok(socket) => {
+                send_packet(&socket, addr, &Packet::<HugEvent>::Hello);
+                self.socket = Some(socket);
+                self.state = LanState::Joining {
+                    connection: Connection::new(addr),
+                    hello: Instant::now(),
+                };
+            }
+            Err(err) => {
+                error!("failed to open a socket: {}", err);
+                self.events.push(HugEvent::NotFound);
+            }
+        }
+    }
+
+    fn leave(&mut self) {
+        if let (Some(socket), LanState::Connected { connection }) = (&self.socket, &self.state) {
+            // Bye is not acknowledged, the peer times out if it's lost.
+            for _ in 0..3 {
+                send_packet(socket, connection.addr, &Packet::<HugEvent>::Bye);
+            }
+        }
+        self.state = LanState::Idle;
+        self.socket = None;
+    }
+}
@@ -1 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod discovery;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lan;
 pub mod share;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod web_socket;
@@ -0,0 +1,148 @@
+use std::{
+    io::ErrorKind,
+    marker::PhantomData,
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use tungstenite::{
+    handshake::{
+        server::{NoCallback, ServerHandshake},
+        HandshakeError, MidHandshake,
+    },
+    Message, WebSocket,
+};
+
+type Handshake = MidHandshake<ServerHandshake<TcpStream, NoCallback>>;
+type HandshakeResult =
+    Result<WebSocket<TcpStream>, HandshakeError<ServerHandshake<TcpStream, NoCallback>>>;
+
+/// Accepts web clients without blocking, finishing their handshakes over several polls.
+pub struct WebSocketListener {
+    listener: TcpListener,
+    handshakes: Vec<Handshake>,
+}
+
+impl WebSocketListener {
+    pub fn bind(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            handshakes: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Clients that finished their handshake since the last poll.
+    pub fn accept<Out: Serialize, In: DeserializeOwned>(
+        &mut self,
+    ) -> Vec<WebSocketConnection<Out, In>> {
+        let mut connections = Vec::new();
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    if let Err(err) = stream.set_nonblocking(true) {
+                        warn!("failed to accept {}: {}", addr, err);
+                        continue;
+                    }
+                    self.finish(tungstenite::accept(stream), &mut connections);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("failed to accept: {}", err);
+                    break;
+                }
+            }
+        }
+        for handshake in self.handshakes.split_off(0) {
+            self.finish(handshake.handshake(), &mut connections);
+        }
+        connections
+    }
+
+    fn finish<Out: Serialize, In: DeserializeOwned>(
+        &mut self,
+        result: HandshakeResult,
+        connections: &mut Vec<WebSocketConnection<Out, In>>,
+    ) {
+        match result {
+            Ok(socket) => match socket.get_ref().peer_addr() {
+                Ok(addr) => connections.push(WebSocketConnection::new(addr, socket)),
+                Err(err) => warn!("failed to accept: {}", err),
+            },
+            Err(HandshakeError::Interrupted(handshake)) => self.handshakes.push(handshake),
+            Err(HandshakeError::Failure(err)) => warn!("failed to accept: {}", err),
+        }
+    }
+}
+
+/// A web client, sending `Out` and receiving `In` as JSON text messages.
+/// Websockets are reliable and ordered, so unlike `Connection` nothing is acknowledged here.
+pub struct WebSocketConnection<Out, In> {
+    pub addr: SocketAddr,
+    socket: WebSocket<TcpStream>,
+    closed: bool,
+    data: PhantomData<(Out, In)>,
+}
+
+impl<Out: Serialize, In: DeserializeOwned> WebSocketConnection<Out, In> {
+    fn new(addr: SocketAddr, socket: WebSocket<TcpStream>) -> Self {
+        Self {
+            addr,
+            socket,
+            closed: false,
+            data: Default::default(),
+        }
+    }
+
+    pub fn send(&mut self, message: Out) {
+        let text = serde_json::to_string(&message).unwrap();
+        // A message that would block stays queued until `flush`.
+        match self.socket.write_message(Message::Text(text)) {
+            Ok(()) => {}
+            Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {}
+            Err(err) => self.close(err),
+        }
+    }
+
+    pub fn receive(&mut self, messages: &mut Vec<In>) {
+        while !self.closed {
+            match self.socket.read_message() {
+                Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                    Ok(message) => messages.push(message),
+                    Err(err) => warn!("invalid message from {}: {}", self.addr, err),
+                },
+                Ok(Message::Close(_)) => self.closed = true,
+                // Pings are answered by `flush`.
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => self.close(err),
+            }
+        }
+    }
+
+    pub fn flush(&mut self) {
+        match self.socket.write_pending() {
+            Ok(()) => {}
+            Err(tungstenite::Error::Io(err)) if err.kind() == ErrorKind::WouldBlock => {}
+            Err(err) => self.close(err),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn close(&mut self, err: tungstenite::Error) {
+        match err {
+            tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {}
+            err => warn!("closing {}: {}", self.addr, err),
+        }
+        self.closed = true;
+    }
+}
@@ -0,0 +1,8 @@
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    hug::server::run();
+}
+
+// Rooms need UDP sockets and threads.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,29 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+/// Where the partner's torso is, for the hand control to draw the hands around it.
+pub struct ReachAssist<T> {
+    /// The center of the partner's chest and spine, in the frame of the chest.
+    torso: Option<Vec3>,
+    data: PhantomData<T>,
+}
+
+impl<T> Default for ReachAssist<T> {
+    fn default() -> Self {
+        Self {
+            torso: None,
+            data: Default::default(),
+        }
+    }
+}
+
+impl<T> ReachAssist<T> {
+    pub fn torso(&self) -> Option<Vec3> {
+        self.torso
+    }
+
+    pub fn set_torso(&mut self, torso: Option<Vec3>) {
+        self.torso = torso;
+    }
+}
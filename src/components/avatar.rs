@@ -0,0 +1,185 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    body::part::BodyPartId,
+    player::{Player1, Player2},
+    skeleton::{Shape, Skeleton},
+};
+
+pub const PALETTE: [Color; 6] = [
+    Color::rgb(0.6, 0.4, 0.1),
+    Color::rgb(0.2, 0.2, 0.7),
+    Color::rgb(0.7, 0.25, 0.3),
+    Color::rgb(0.25, 0.55, 0.3),
+    Color::rgb(0.55, 0.3, 0.6),
+    Color::rgb(0.85, 0.75, 0.6),
+];
+pub const MIN_SCALE: f32 = 0.8;
+pub const MAX_SCALE: f32 = 1.2;
+
+/// How a player looks, chosen by the player and sent to the partner.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Avatar {
+    /// Index into `PALETTE`.
+    pub color: u8,
+    /// Scale of the whole body.
+    pub height: f32,
+    /// Scale of the arms and legs, relative to the torso.
+    pub proportion: f32,
+    pub head: HeadShape,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HeadShape {
+    Box,
+    Round,
+    Long,
+}
+
+impl HeadShape {
+    pub fn next(self) -> Self {
+        match self {
+            HeadShape::Box => HeadShape::Round,
+            HeadShape::Round => HeadShape::Long,
+            HeadShape::Long => HeadShape::Box,
+        }
+    }
+}
+
+impl Default for Avatar {
+    fn default() -> Self {
+        Self {
+            color: 0,
+            height: 1.0,
+            proportion: 1.0,
+            head: HeadShape::Box,
+        }
+    }
+}
+
+impl Avatar {
+    pub fn color(&self) -> Color {
+        PALETTE[self.color as usize % PALETTE.len()]
+    }
+
+    /// Whether both build the same ragdoll, whatever their colors.
+    pub fn same_shape(&self, other: &Self) -> bool {
+        self.height == other.height
+            && self.proportion == other.proportion
+            && self.head == other.head
+    }
+
+    /// Keeps settings edited by hand within the ranges the partner accepts.
+    pub fn clamped(mut self) -> Self {
+        self.color %= PALETTE.len() as u8;
+        for scale in [&mut self.height, &mut self.proportion] {
+            *scale = if scale.is_finite() {
+                scale.clamp(MIN_SCALE, MAX_SCALE)
+            } else {
+                1.0
+            };
+        }
+        self
+    }
+
+    /// The skeleton resized and reshaped for this avatar.
+    pub fn apply(&self, skeleton: &Skeleton) -> Skeleton {
+        use BodyPartId::*;
+        let mut skeleton = skeleton.clone();
+        skeleton.ratio *= self.height;
+        for part in skeleton.parts.iter_mut() {
+            match part.name {
+                // The hips stand as high as the legs are long.
+                Hip | ForearmLeft | HandLeft | ForearmRight | HandRight | ShinLeft | FootLeft
+                | ShinRight | FootRight => {
+                    for offset in part.offset.iter_mut() {
+                        *offset *= self.proportion;
+                    }
+                }
+                Head => match (self.head, part.shape) {
+                    (HeadShape::Round, Shape::Bone { width, .. }) => {
+                        part.shape = Shape::Ball {
+                            radius: width / 2.0,
+                        };
+                    }
+                    (HeadShape::Long, Shape::Bone { width, thickness }) => {
+                        part.shape = Shape::Bone {
+                            width: width * 0.85,
+                            thickness: thickness * 0.85,
+                        };
+                        part.offset[1] *= 1.2;
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        skeleton
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    const PATH: &'static str = "avatar.json";
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::PATH)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .unwrap_or_default()
+            .clamped()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        if let Err(err) = std::fs::write(Self::PATH, serde_json::to_string(self).unwrap()) {
+            error!("failed to save the avatar: {}", err);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        storage
+            .get_item("avatar")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .unwrap_or_default()
+            .clamped()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        let _ = storage.set_item("avatar", &serde_json::to_string(self).unwrap());
+    }
+}
+
+pub struct PlayerAvatar<T>(pub Avatar, PhantomData<T>);
+
+impl<T> PlayerAvatar<T> {
+    pub fn new(avatar: Avatar) -> Self {
+        Self(avatar, PhantomData)
+    }
+}
+
+impl Default for PlayerAvatar<Player1> {
+    fn default() -> Self {
+        Self::new(Avatar::default())
+    }
+}
+
+impl Default for PlayerAvatar<Player2> {
+    fn default() -> Self {
+        Self::new(Avatar {
+            color: 1,
+            ..Default::default()
+        })
+    }
+}
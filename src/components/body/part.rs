@@ -1,54 +1,78 @@
+use bevy::ecs::system::EntityCommands;
+use serde::{Deserialize, Serialize};
+
 use super::BodyPart;
 
-#[derive(Default)]
-pub struct Hip;
-#[derive(Default)]
-pub struct Spine;
-#[derive(Default)]
-pub struct Chest;
-#[derive(Default)]
-pub struct Neck;
-#[derive(Default)]
-pub struct Head;
-#[derive(Default)]
-pub struct UpperArmLeft;
-#[derive(Default)]
-pub struct ForearmLeft;
-#[derive(Default)]
-pub struct HandLeft;
-#[derive(Default)]
-pub struct UpperArmRight;
-#[derive(Default)]
-pub struct ForearmRight;
-#[derive(Default)]
-pub struct HandRight;
-#[derive(Default)]
-pub struct ThighLeft;
-#[derive(Default)]
-pub struct ShinLeft;
-#[derive(Default)]
-pub struct FootLeft;
-#[derive(Default)]
-pub struct ThighRight;
-#[derive(Default)]
-pub struct ShinRight;
-#[derive(Default)]
-pub struct FootRight;
-
-impl BodyPart for Hip {}
-impl BodyPart for Spine {}
-impl BodyPart for Chest {}
-impl BodyPart for Neck {}
-impl BodyPart for Head {}
-impl BodyPart for UpperArmLeft {}
-impl BodyPart for ForearmLeft {}
-impl BodyPart for HandLeft {}
-impl BodyPart for UpperArmRight {}
-impl BodyPart for ForearmRight {}
-impl BodyPart for HandRight {}
-impl BodyPart for ThighLeft {}
-impl BodyPart for ShinLeft {}
-impl BodyPart for FootLeft {}
-impl BodyPart for ThighRight {}
-impl BodyPart for ShinRight {}
-impl BodyPart for FootRight {}
+macro_rules! body_parts {
+    ($($part:ident),* $(,)?) => {
+        $(
+            #[derive(Default)]
+            pub struct $part;
+
+            impl BodyPart for $part {
+                const ID: BodyPartId = BodyPartId::$part;
+            }
+        )*
+
+        pub const PART_COUNT: usize = [$(stringify!($part)),*].len();
+
+        /// Addresses a body part at runtime, e.g. in skeletons, poses and payloads.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum BodyPartId {
+            $($part),*
+        }
+
+        impl BodyPartId {
+            pub const ALL: [BodyPartId; PART_COUNT] = [$(BodyPartId::$part),*];
+
+            pub fn index(self) -> usize {
+                self as usize
+            }
+
+            pub fn insert_marker(self, entity: &mut EntityCommands) {
+                match self {
+                    $(BodyPartId::$part => {
+                        entity.insert($part);
+                    })*
+                }
+            }
+        }
+    };
+}
+
+body_parts!(
+    Hip,
+    Spine,
+    Chest,
+    Neck,
+    Head,
+    UpperArmLeft,
+    ForearmLeft,
+    HandLeft,
+    UpperArmRight,
+    ForearmRight,
+    HandRight,
+    ThighLeft,
+    ShinLeft,
+    FootLeft,
+    ThighRight,
+    ShinRight,
+    FootRight,
+    PalmLeft,
+    FingersLeft,
+    FingertipsLeft,
+    PalmRight,
+    FingersRight,
+    FingertipsRight,
+);
+
+impl BodyPartId {
+    /// Parts a skeleton may leave out, like fingers.
+    pub fn is_optional(self) -> bool {
+        use BodyPartId::*;
+        matches!(
+            self,
+            PalmLeft | FingersLeft | FingertipsLeft | PalmRight | FingersRight | FingertipsRight
+        )
+    }
+}
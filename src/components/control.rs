@@ -2,7 +2,11 @@ use std::{f32::consts::PI, marker::PhantomData};
 
 use bevy::prelude::*;
 
-use super::player::{Player, Player1, Player2};
+use super::{
+    body::{part::BodyPartId, Body},
+    ik::{ArmChain, ShoulderLimits},
+    player::Player,
+};
 
 const MIN: f32 = 0.0;
 const MAX: f32 = 1.0;
@@ -11,6 +15,17 @@ pub struct HandControl<T> {
     data: PhantomData<T>,
     left: Vec2,
     right: Vec2,
+    /// How far the fingers of each hand are curled, from 0 to 1.
+    grip: Vec2,
+    /// Leaning forward in x, leaning right in y and twisting left in z, from -1 to 1.
+    torso: Vec3,
+    /// Looking right in x and up in y from -1 to 1, instead of at the partner.
+    look: Option<Vec2>,
+    /// In the targeting mode, how far each hand reaches forward from 0 to 1, left in x and
+    /// right in y. `left` and `right` then place the hands instead of turning the arms.
+    reach: Option<Vec2>,
+    /// How strongly the hands are drawn around the partner's torso, from 0 (off) to 1.
+    assist: f32,
 }
 
 impl<T> Default for HandControl<T> {
@@ -19,6 +34,11 @@ impl<T> Default for HandControl<T> {
             data: Default::default(),
             left: Vec2::new(0.0 + 0.09, 0.1),
             right: Vec2::new(1.0 - 0.09, 0.1),
+            grip: Vec2::ZERO,
+            torso: Vec3::ZERO,
+            look: None,
+            reach: None,
+            assist: 0.0,
         }
     }
 }
@@ -29,6 +49,11 @@ impl<T> HandControl<T> {
             data: Default::default(),
             left: Vec2::new(0.0, 0.5),
             right: Vec2::new(1.0, 0.5),
+            grip: Vec2::ZERO,
+            torso: Vec3::ZERO,
+            look: None,
+            reach: None,
+            assist: 0.0,
         }
     }
 }
@@ -37,6 +62,16 @@ const SHOULDER_Y_MAX: f32 = PI * 4.0 / 7.0 / 2.0;
 const ELBOW_Y_MAX: f32 = PI * 3.8 / 7.0 / 2.0;
 const SHOULDER_Z_MIN: f32 = -PI * 8.0 / 9.0 / 4.0;
 const SHOULDER_Z_MAX: f32 = PI * 8.0 / 9.0 / 4.0;
+/// The arm angles never twist the upper arm, but placed hands may, to point the elbow down.
+const SHOULDER_X_MAX: f32 = PI / 4.0;
+const KNUCKLE_MAX: f32 = PI * 4.0 / 9.0;
+const FINGER_MAX: f32 = PI * 3.0 / 9.0;
+const LEAN_FORWARD_MAX: f32 = PI / 6.0;
+const LEAN_SIDE_MAX: f32 = PI / 12.0;
+const TWIST_MAX: f32 = PI / 6.0;
+/// Where the assisted hands hold the partner, beside and behind the center of their torso.
+const WRAP_WIDTH: f32 = 0.12;
+const WRAP_DEPTH: f32 = 0.08;
 
 impl<T: Player> HandControl<T> {
     pub fn left(&self) -> &Vec2 {
@@ -48,27 +83,69 @@ impl<T: Player> HandControl<T> {
     }
 
     pub fn set_left(&mut self, left: Vec2) {
-        self.left = left;
+        self.left = clamp(left);
     }
 
     pub fn set_right(&mut self, right: Vec2) {
-        self.right = right;
+        self.right = clamp(right);
     }
 
     pub fn add_left(&mut self, other: Vec2) {
-        self.left += other;
-        self.left.x = self.left.x.min(MAX).max(MIN);
-        self.left.y = self.left.y.min(MAX).max(MIN);
+        self.left = clamp(self.left + other);
     }
 
     pub fn add_right(&mut self, other: Vec2) {
-        self.right += other;
-        self.right.x = self.right.x.min(MAX).max(MIN);
-        self.right.y = self.right.y.min(MAX).max(MIN);
+        self.right = clamp(self.right + other);
+    }
+
+    /// Left grip in x, right grip in y.
+    pub fn grip(&self) -> &Vec2 {
+        &self.grip
+    }
+
+    pub fn set_grip(&mut self, grip: Vec2) {
+        self.grip = clamp(grip);
+    }
+
+    /// Leaning forward in x, leaning right in y and twisting left in z.
+    pub fn torso(&self) -> &Vec3 {
+        &self.torso
+    }
+
+    pub fn set_torso(&mut self, torso: Vec3) {
+        self.torso = torso.max(-Vec3::ONE).min(Vec3::ONE);
+    }
+
+    pub fn look(&self) -> Option<Vec2> {
+        self.look
+    }
+
+    pub fn set_look(&mut self, look: Option<Vec2>) {
+        self.look = look.map(|look| look.max(-Vec2::ONE).min(Vec2::ONE));
+    }
+
+    pub fn reach(&self) -> Option<Vec2> {
+        self.reach
+    }
+
+    pub fn set_reach(&mut self, reach: Option<Vec2>) {
+        self.reach = reach.map(clamp);
+    }
+
+    pub fn add_reach(&mut self, other: Vec2) {
+        if let Some(reach) = self.reach {
+            self.reach = Some(clamp(reach + other));
+        }
+    }
+
+    pub fn assist(&self) -> f32 {
+        self.assist
+    }
+
+    pub fn set_assist(&mut self, assist: f32) {
+        self.assist = assist.min(MAX).max(MIN);
     }
-}
 
-impl HandControl<Player1> {
     pub fn right_sholder(&self) -> (f32, f32, f32) {
         let x = 0.0;
         let y = lerp(0.0, SHOULDER_Y_MAX, 1.0 - self.right.x);
@@ -96,38 +173,170 @@ impl HandControl<Player1> {
         let z = 0.0;
         (x, y, z)
     }
-}
 
-impl HandControl<Player2> {
-    pub fn right_sholder(&self) -> (f32, f32, f32) {
-        let x = 0.0;
-        let y = lerp(0.0, SHOULDER_Y_MAX, 1.0 - self.right.x);
-        let z = lerp(SHOULDER_Z_MAX, SHOULDER_Z_MIN, self.right.y);
-        (x, y, z)
+    /// Rotations of the torso, arms and fingers, layered on top of the base pose.
+    /// `partner` is the center of the partner's torso in the frame of the chest, if known.
+    pub fn layer(&self, body: &Body, partner: Option<Vec3>) -> [(BodyPartId, Quat); 9] {
+        let rotation = |(b, a, c): (f32, f32, f32)| Quat::from_rotation_ypr(a, b, c);
+        let [palm_left, fingers_left, palm_right, fingers_right] = self.grip_layer();
+        let angles = [
+            (BodyPartId::UpperArmLeft, rotation(self.left_sholder())),
+            (BodyPartId::UpperArmRight, rotation(self.right_sholder())),
+            (BodyPartId::ForearmLeft, rotation(self.left_elbow())),
+            (BodyPartId::ForearmRight, rotation(self.right_elbow())),
+        ];
+        let [shoulder_left, shoulder_right, elbow_left, elbow_right] =
+            self.arm_layer(body, angles, partner);
+        [
+            self.torso_layer(),
+            shoulder_left,
+            shoulder_right,
+            elbow_left,
+            elbow_right,
+            palm_left,
+            fingers_left,
+            palm_right,
+            fingers_right,
+        ]
     }
 
-    pub fn left_sholder(&self) -> (f32, f32, f32) {
-        let x = 0.0;
-        let y = lerp(0.0, -SHOULDER_Y_MAX, self.left.x);
-        let z = lerp(-SHOULDER_Z_MAX, -SHOULDER_Z_MIN, self.left.y);
-        (x, y, z)
+    /// Rotations of the shoulders and elbows, from the arm angles or, in the targeting mode,
+    /// from where the hands are placed in front of the chest. The assist then draws the hands
+    /// toward wrapping around the partner's torso, given in the frame of the chest.
+    fn arm_layer(
+        &self,
+        body: &Body,
+        angles: [(BodyPartId, Quat); 4],
+        partner: Option<Vec3>,
+    ) -> [(BodyPartId, Quat); 4] {
+        // Bodies face -z; elbows point down and back.
+        let pole = Vec3::new(0.0, -1.0, 0.5).normalize();
+        let arm = |upper_arm: BodyPartId,
+                   forearm: BodyPartId,
+                   hand: BodyPartId,
+                   input: (f32, f32, f32),
+                   angles: (Quat, Quat)| {
+            let upper = body.part(forearm).translation;
+            let side = upper.x.signum();
+            // The arm angles turn each shoulder forward, one way for each side.
+            let chain = ArmChain {
+                shoulder: body.part(upper_arm).translation,
+                upper,
+                lower: body.part(hand).translation,
+                hinge: side,
+                limits: ShoulderLimits {
+                    yaw: (
+                        SHOULDER_Y_MAX * side.min(0.0),
+                        SHOULDER_Y_MAX * side.max(0.0),
+                    ),
+                    pitch: (-SHOULDER_X_MAX, SHOULDER_X_MAX),
+                    roll: (SHOULDER_Z_MIN, SHOULDER_Z_MAX),
+                },
+            };
+            let length = chain.upper.length() + chain.lower.length();
+            // The pull fades out as the partner gets out of reach.
+            let wrap =
+                partner.map(|partner| partner + Vec3::new(side * WRAP_WIDTH, 0.0, -WRAP_DEPTH));
+            let weight = wrap.map_or(0.0, |wrap| {
+                let distance = wrap.distance(chain.shoulder);
+                self.assist * (2.0 - distance / length).min(1.0).max(0.0)
+            });
+            if self.reach.is_some() {
+                let (open, height, reach) = input;
+                let target = Vec3::new(
+                    side * lerp(-0.1 * length, chain.shoulder.x.abs() + 0.8 * length, open),
+                    chain.shoulder.y + lerp(-0.8 * length, 0.6 * length, height),
+                    -lerp(0.1 * length, 0.9 * length, reach),
+                );
+                return chain.solve(wrap.map_or(target, |wrap| target.lerp(wrap, weight)), pole);
+            }
+            // Turned arms blend their rotations toward wrapping instead, so they don't jump when
+            // the pull sets in.
+            match wrap {
+                Some(wrap) if weight > 0.0 => {
+                    let (shoulder, elbow) = chain.solve(wrap, pole);
+                    (
+                        blend(angles.0, shoulder, weight),
+                        blend(angles.1, elbow, weight),
+                    )
+                }
+                _ => angles,
+            }
+        };
+        let [shoulder_left, shoulder_right, elbow_left, elbow_right] = angles;
+        let reach = self.reach.unwrap_or_default();
+        // Like the arm angles, the left hand opens toward 0 and the right hand toward 1.
+        let (shoulder_left, elbow_left) = arm(
+            BodyPartId::UpperArmLeft,
+            BodyPartId::ForearmLeft,
+            BodyPartId::HandLeft,
+            (1.0 - self.left.x, self.left.y, reach.x),
+            (shoulder_left.1, elbow_left.1),
+        );
+        let (shoulder_right, elbow_right) = arm(
+            BodyPartId::UpperArmRight,
+            BodyPartId::ForearmRight,
+            BodyPartId::HandRight,
+            (self.right.x, self.right.y, reach.y),
+            (shoulder_right.1, elbow_right.1),
+        );
+        [
+            (BodyPartId::UpperArmLeft, shoulder_left),
+            (BodyPartId::UpperArmRight, shoulder_right),
+            (BodyPartId::ForearmLeft, elbow_left),
+            (BodyPartId::ForearmRight, elbow_right),
+        ]
     }
 
-    pub fn right_elbow(&self) -> (f32, f32, f32) {
-        let x = 0.0;
-        let y = lerp(0.0, ELBOW_Y_MAX, (1.0 - self.right.x).powi(2));
-        let z = 0.0;
-        (x, y, z)
+    /// Bends the chest over the spine, through the motor of the joint between them.
+    /// Bodies face -z, so leaning forward and right are negative rotations.
+    fn torso_layer(&self) -> (BodyPartId, Quat) {
+        let rotation = Quat::from_rotation_ypr(
+            self.torso.z * TWIST_MAX,
+            -self.torso.x * LEAN_FORWARD_MAX,
+            -self.torso.y * LEAN_SIDE_MAX,
+        );
+        (BodyPartId::Spine, rotation)
     }
 
-    pub fn left_elbow(&self) -> (f32, f32, f32) {
-        let x = 0.0;
-        let y = lerp(0.0, -ELBOW_Y_MAX, self.left.x.powi(2));
-        let z = 0.0;
-        (x, y, z)
+    /// Curls the fingers toward the front, the way the arms close.
+    fn grip_layer(&self) -> [(BodyPartId, Quat); 4] {
+        let curl = |grip: f32, max: f32, sign: f32| Quat::from_rotation_y(sign * grip * max);
+        [
+            (BodyPartId::PalmLeft, curl(self.grip.x, KNUCKLE_MAX, -1.0)),
+            (BodyPartId::FingersLeft, curl(self.grip.x, FINGER_MAX, -1.0)),
+            (BodyPartId::PalmRight, curl(self.grip.y, KNUCKLE_MAX, 1.0)),
+            (BodyPartId::FingersRight, curl(self.grip.y, FINGER_MAX, 1.0)),
+        ]
     }
 }
 
+/// Turns `from` toward `to` by `weight`, the short way around.
+fn blend(from: Quat, to: Quat, weight: f32) -> Quat {
+    let to = if from.dot(to) < 0.0 { -to } else { to };
+    from.slerp(to, weight)
+}
+
+pub fn is_in_range(value: &Vec2) -> bool {
+    (MIN..=MAX).contains(&value.x) && (MIN..=MAX).contains(&value.y)
+}
+
+pub fn is_torso_in_range(value: &Vec3) -> bool {
+    value.abs().max_element() <= 1.0
+}
+
+pub fn is_look_in_range(value: &Vec2) -> bool {
+    value.abs().max_element() <= 1.0
+}
+
+pub fn is_assist_in_range(value: f32) -> bool {
+    (MIN..=MAX).contains(&value)
+}
+
+fn clamp(value: Vec2) -> Vec2 {
+    Vec2::new(value.x.min(MAX).max(MIN), value.y.min(MAX).max(MIN))
+}
+
 fn lerp(min: f32, max: f32, t: f32) -> f32 {
     min + (max - min) * t
 }
@@ -0,0 +1,120 @@
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use super::body::part::BodyPartId;
+
+/// Grip above which a hand holds on to what it touches.
+pub const GRAB_GRIP: f32 = 0.5;
+/// Force in newtons that tears a hand off the partner.
+pub const BREAK_FORCE: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    pub const BOTH: [Hand; 2] = [Hand::Left, Hand::Right];
+
+    /// Parts of the hand that can grab; the first existing one holds.
+    pub fn parts(self) -> [BodyPartId; 4] {
+        use BodyPartId::*;
+        match self {
+            Hand::Left => [PalmLeft, FingersLeft, FingertipsLeft, HandLeft],
+            Hand::Right => [PalmRight, FingersRight, FingertipsRight, HandRight],
+        }
+    }
+
+    /// The side of an arm part, from the shoulder to the fingertips.
+    pub fn of_arm(part: BodyPartId) -> Option<Self> {
+        use BodyPartId::*;
+        match part {
+            UpperArmLeft | ForearmLeft | HandLeft | PalmLeft | FingersLeft | FingertipsLeft => {
+                Some(Hand::Left)
+            }
+            UpperArmRight | ForearmRight | HandRight | PalmRight | FingersRight
+            | FingertipsRight => Some(Hand::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Parts of the partner that can be held: the torso, the arms and the head.
+pub fn is_grabbable(part: BodyPartId) -> bool {
+    use BodyPartId::*;
+    matches!(
+        part,
+        Spine
+            | Chest
+            | Neck
+            | Head
+            | UpperArmLeft
+            | ForearmLeft
+            | HandLeft
+            | UpperArmRight
+            | ForearmRight
+            | HandRight
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Grab {
+    Free,
+    Holding(BodyPartId),
+    /// Torn off; free again once the grip opens.
+    Broken,
+}
+
+impl Grab {
+    pub fn target(self) -> Option<BodyPartId> {
+        match self {
+            Grab::Holding(part) => Some(part),
+            _ => None,
+        }
+    }
+
+    pub fn from_target(target: Option<BodyPartId>) -> Self {
+        target.map(Grab::Holding).unwrap_or(Grab::Free)
+    }
+}
+
+/// What each hand of a player holds of the partner.
+pub struct Grabs<T> {
+    pub left: Grab,
+    pub right: Grab,
+    data: PhantomData<T>,
+}
+
+impl<T> Default for Grabs<T> {
+    fn default() -> Self {
+        Self {
+            left: Grab::Free,
+            right: Grab::Free,
+            data: PhantomData,
+        }
+    }
+}
+
+impl<T> Grabs<T> {
+    pub fn get(&self, hand: Hand) -> Grab {
+        match hand {
+            Hand::Left => self.left,
+            Hand::Right => self.right,
+        }
+    }
+
+    pub fn get_mut(&mut self, hand: Hand) -> &mut Grab {
+        match hand {
+            Hand::Left => &mut self.left,
+            Hand::Right => &mut self.right,
+        }
+    }
+}
+
+/// The joint holding a hand to a part of the partner.
+pub struct GrabJoint {
+    pub hand: Hand,
+    pub target: BodyPartId,
+}
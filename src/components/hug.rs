@@ -0,0 +1,148 @@
+use std::{collections::HashSet, time::Duration};
+
+use super::{body::part::BodyPartId, grab::Hand};
+
+/// A hug has to hold this long to start, and be lost this long to end.
+pub const HUG_START_DELAY: Duration = Duration::from_millis(300);
+pub const HUG_END_DELAY: Duration = Duration::from_millis(500);
+
+/// Contact points and part pairs that make a full embrace.
+pub const FULL_CONTACT_POINTS: f32 = 24.0;
+pub const FULL_CONTACT_PAIRS: f32 = 8.0;
+/// Distances between the chests that score fully and not at all.
+pub const CHEST_NEAR: f32 = 0.2;
+pub const CHEST_FAR: f32 = 0.6;
+/// Relative speed of the chests that halves the stability.
+pub const UNSTABLE_SPEED: f32 = 0.3;
+
+/// Pairs of a part of player1 and a part of player2 that touch.
+#[derive(Default)]
+pub struct PlayerContacts(pub HashSet<(BodyPartId, BodyPartId)>);
+
+impl PlayerContacts {
+    /// Whether player1 wraps both arms around the torso of player2.
+    pub fn player1_wraps(&self) -> bool {
+        wraps(self.0.iter().copied())
+    }
+
+    /// Whether player2 wraps both arms around the torso of player1.
+    pub fn player2_wraps(&self) -> bool {
+        wraps(self.0.iter().map(|(part1, part2)| (*part2, *part1)))
+    }
+
+    /// How evenly both arms of each player touch the partner, from 0 to 1.
+    pub fn symmetry(&self) -> f32 {
+        let player1 = symmetry(self.0.iter().map(|(part1, _)| *part1));
+        let player2 = symmetry(self.0.iter().map(|(_, part2)| *part2));
+        (player1 + player2) / 2.0
+    }
+}
+
+fn symmetry(parts: impl Iterator<Item = BodyPartId>) -> f32 {
+    let (mut left, mut right) = (0.0, 0.0);
+    for part in parts {
+        match Hand::of_arm(part) {
+            Some(Hand::Left) => left += 1.0,
+            Some(Hand::Right) => right += 1.0,
+            None => {}
+        }
+    }
+    if left + right == 0.0 {
+        return 0.0;
+    }
+    1.0 - (left - right).abs() / (left + right)
+}
+
+fn wraps(contacts: impl Iterator<Item = (BodyPartId, BodyPartId)> + Clone) -> bool {
+    Hand::BOTH.iter().all(|hand| {
+        contacts.clone().any(|(arm, torso)| {
+            Hand::of_arm(arm) == Some(*hand)
+                && matches!(torso, BodyPartId::Chest | BodyPartId::Spine)
+        })
+    })
+}
+
+pub struct HugStarted;
+
+/// Qualities of a hug at one moment, each from 0 to 1.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HugSample {
+    /// How many points and pairs of parts touch.
+    pub contact: f32,
+    pub symmetry: f32,
+    /// How close the chests are.
+    pub closeness: f32,
+    /// How still the chests are relative to each other.
+    pub stability: f32,
+}
+
+impl HugSample {
+    pub fn new(
+        points: usize,
+        contacts: &PlayerContacts,
+        chest_distance: f32,
+        chest_speed: f32,
+    ) -> Self {
+        let points = (points as f32 / FULL_CONTACT_POINTS).min(1.0);
+        let pairs = (contacts.0.len() as f32 / FULL_CONTACT_PAIRS).min(1.0);
+        Self {
+            contact: (points + pairs) / 2.0,
+            symmetry: contacts.symmetry(),
+            closeness: ((CHEST_FAR - chest_distance) / (CHEST_FAR - CHEST_NEAR)).clamp(0.0, 1.0),
+            stability: 1.0 / (1.0 + chest_speed / UNSTABLE_SPEED),
+        }
+    }
+
+    /// The weighted score, from 0 to 100.
+    pub fn score(&self) -> f32 {
+        (self.contact * 0.35 + self.symmetry * 0.2 + self.closeness * 0.25 + self.stability * 0.2)
+            * 100.0
+    }
+}
+
+/// Score of the ongoing hug, or of the last one once it ended.
+#[derive(Default)]
+pub struct HugScore {
+    pub sample: HugSample,
+    /// Average of the samples over the hug, from 0 to 100.
+    pub score: f32,
+    pub best: f32,
+    /// Seconds sampled so far.
+    pub seconds: f32,
+}
+
+impl HugScore {
+    pub fn add(&mut self, sample: HugSample, seconds: f32) {
+        self.sample = sample;
+        self.seconds += seconds;
+        if self.seconds > 0.0 {
+            self.score += (sample.score() - self.score) * seconds / self.seconds;
+        }
+    }
+
+    /// Starts scoring a new hug, keeping the best score.
+    pub fn restart(&mut self) {
+        *self = Self {
+            best: self.best,
+            ..Default::default()
+        };
+    }
+}
+
+pub struct HugEnded {
+    pub duration: Duration,
+}
+
+/// Hugs with the current partner.
+#[derive(Default)]
+pub struct HugStats {
+    pub hugging: bool,
+    /// Length of the ongoing hug.
+    pub current: Duration,
+    /// Time spent hugging, which is what sharing reports.
+    pub total: Duration,
+    pub count: u32,
+    pub longest: Duration,
+    /// How long the contacts have disagreed with `hugging`.
+    pub pending: Duration,
+}
@@ -0,0 +1,87 @@
+use std::{f32::consts::PI, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use super::body::part::BodyPartId;
+
+/// Procedural idle motion layered between the base pose and `HandControl`.
+pub struct IdleAnimation<T> {
+    pub enabled: bool,
+    /// Breaths per second, and how far the chest bends with them in radians.
+    pub breathing_rate: f32,
+    pub breathing_depth: f32,
+    /// Swaying of the head, in cycles per second and radians.
+    pub sway_rate: f32,
+    pub sway_angle: f32,
+    /// Shifting the weight between the legs, in cycles per second and meters.
+    pub shift_rate: f32,
+    pub shift_distance: f32,
+    /// Seconds added to the clock, so the players don't move in unison.
+    pub phase: f32,
+    elapsed: f32,
+    data: PhantomData<T>,
+}
+
+impl<T> IdleAnimation<T> {
+    pub fn new(phase: f32) -> Self {
+        Self {
+            enabled: true,
+            breathing_rate: 0.25,
+            breathing_depth: 0.02,
+            sway_rate: 0.1,
+            sway_angle: 0.04,
+            shift_rate: 0.05,
+            shift_distance: 0.02,
+            phase,
+            elapsed: 0.0,
+            data: Default::default(),
+        }
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+    }
+
+    fn wave(&self, rate: f32, offset: f32) -> f32 {
+        (2.0 * PI * rate * (self.elapsed + self.phase) + offset).sin()
+    }
+
+    /// Rotations of the torso and neck; the hips move with `shift`.
+    pub fn layer(&self) -> [(BodyPartId, Quat); 3] {
+        if !self.enabled {
+            return [
+                (BodyPartId::Spine, Quat::IDENTITY),
+                (BodyPartId::Chest, Quat::IDENTITY),
+                (BodyPartId::Neck, Quat::IDENTITY),
+            ];
+        }
+        let breath = self.wave(self.breathing_rate, 0.0) * self.breathing_depth;
+        let sway = self.wave(self.sway_rate, 0.0) * self.sway_angle;
+        let nod = self.wave(self.sway_rate * 0.63, 1.3) * self.sway_angle * 0.5;
+        // The spine leans against the hips, keeping the head above the feet.
+        let lean = -self.shift().x * 2.0;
+        [
+            (
+                BodyPartId::Spine,
+                Quat::from_rotation_z(lean) * Quat::from_rotation_x(-breath * 0.5),
+            ),
+            (BodyPartId::Chest, Quat::from_rotation_x(breath)),
+            (
+                BodyPartId::Neck,
+                Quat::from_rotation_z(sway) * Quat::from_rotation_x(nod),
+            ),
+        ]
+    }
+
+    /// Sideways offset of the hips.
+    pub fn shift(&self) -> Vec3 {
+        if !self.enabled {
+            return Vec3::ZERO;
+        }
+        Vec3::new(
+            self.wave(self.shift_rate, 0.0) * self.shift_distance,
+            0.0,
+            0.0,
+        )
+    }
+}
@@ -0,0 +1,252 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+/// How far an elbow bends at most, in radians.
+pub const MAX_ELBOW_BEND: f32 = PI * 0.85;
+
+/// How far a shoulder turns, as ranges of the angles of `Quat::from_rotation_ypr`.
+#[derive(Clone, Copy)]
+pub struct ShoulderLimits {
+    pub yaw: (f32, f32),
+    pub pitch: (f32, f32),
+    pub roll: (f32, f32),
+}
+
+impl ShoulderLimits {
+    /// The nearest rotation within the limits.
+    pub fn clamp(&self, rotation: Quat) -> Quat {
+        let (yaw, pitch, roll) = to_ypr(rotation);
+        let clamp = |angle: f32, (min, max): (f32, f32)| angle.clamp(min, max);
+        Quat::from_rotation_ypr(
+            clamp(yaw, self.yaw),
+            clamp(pitch, self.pitch),
+            clamp(roll, self.roll),
+        )
+    }
+}
+
+/// A shoulder, elbow and wrist, given by the offsets of the elbow from the shoulder and of the
+/// wrist from the elbow at rest.
+pub struct ArmChain {
+    pub shoulder: Vec3,
+    pub upper: Vec3,
+    pub lower: Vec3,
+    /// The elbow bends about y; this is the sign of the angle that bends it forward.
+    pub hinge: f32,
+    pub limits: ShoulderLimits,
+}
+
+impl ArmChain {
+    /// Rotations of the shoulder and the elbow that bring the wrist as close to `target` as
+    /// the arm reaches, with the elbow toward `pole`. A shoulder beyond its limits is held at
+    /// them, and the elbow bends to make up for it as far as it can.
+    pub fn solve(&self, target: Vec3, pole: Vec3) -> (Quat, Quat) {
+        let (shoulder, elbow) = self.solve_free(target, pole);
+        let limited = self.limits.clamp(shoulder);
+        if limited.dot(shoulder).abs() > 1.0 - 1e-6 {
+            return (shoulder, elbow);
+        }
+        // The elbow turns the forearm toward the target, as seen from the elbow in the frame of
+        // the upper arm.
+        let local = reject(
+            limited.inverse() * (target - self.shoulder) - self.upper,
+            Vec3::Y,
+        );
+        if local.length() < 0.0001 {
+            return (limited, elbow);
+        }
+        let bend = self.hinge * signed_angle(reject(self.lower, Vec3::Y), local, Vec3::Y);
+        // Past straight, the nearer end of the range may be the other one.
+        let bend = if bend < 0.0 && bend + 2.0 * PI - MAX_ELBOW_BEND < -bend {
+            MAX_ELBOW_BEND
+        } else {
+            bend.clamp(0.0, MAX_ELBOW_BEND)
+        };
+        (limited, Quat::from_rotation_y(self.hinge * bend))
+    }
+
+    fn solve_free(&self, target: Vec3, pole: Vec3) -> (Quat, Quat) {
+        let (upper, lower) = (self.upper.length(), self.lower.length());
+        let to_target = target - self.shoulder;
+        let distance = to_target
+            .length()
+            .max((upper - lower).abs() + 0.001)
+            .min(upper + lower - 0.001);
+        // The law of cosines gives the inner angle of the elbow.
+        let cos = (upper * upper + lower * lower - distance * distance) / (2.0 * upper * lower);
+        let bend = (PI - cos.clamp(-1.0, 1.0).acos()).min(MAX_ELBOW_BEND);
+        let elbow = Quat::from_rotation_y(self.hinge * bend);
+
+        // Point the bent arm at the target, then roll it about that line toward the pole.
+        let wrist = self.upper + elbow * self.lower;
+        let direction = if to_target.length() > 0.001 {
+            to_target.normalize()
+        } else {
+            wrist.normalize()
+        };
+        let align = rotation_arc(wrist.normalize(), direction);
+        let roll = signed_angle(
+            reject(align * self.upper, direction),
+            reject(pole, direction),
+            direction,
+        );
+        let shoulder = Quat::from_axis_angle(direction, roll) * align;
+        (shoulder, elbow)
+    }
+
+    /// Where the wrist is, with the shoulder and the elbow turned.
+    pub fn wrist(&self, shoulder: Quat, elbow: Quat) -> Vec3 {
+        self.shoulder + shoulder * (self.upper + elbow * self.lower)
+    }
+}
+
+/// Yaw, pitch and roll of `rotation`, the inverse of `Quat::from_rotation_ypr`.
+fn to_ypr(rotation: Quat) -> (f32, f32, f32) {
+    let matrix = Mat3::from_quat(rotation);
+    let pitch = (-matrix.z_axis.y).clamp(-1.0, 1.0).asin();
+    let yaw = matrix.z_axis.x.atan2(matrix.z_axis.z);
+    let roll = matrix.x_axis.y.atan2(matrix.y_axis.y);
+    (yaw, pitch, roll)
+}
+
+/// The shortest rotation from `from` to `to`, both normalized.
+fn rotation_arc(from: Vec3, to: Vec3) -> Quat {
+    let axis = from.cross(to);
+    let dot = from.dot(to).clamp(-1.0, 1.0);
+    if axis.length() < 0.0001 {
+        if dot > 0.0 {
+            return Quat::IDENTITY;
+        }
+        // Opposite vectors turn about any perpendicular axis.
+        let perpendicular = if from.x.abs() < 0.9 { Vec3::X } else { Vec3::Y };
+        return Quat::from_axis_angle(from.cross(perpendicular).normalize(), PI);
+    }
+    Quat::from_axis_angle(axis.normalize(), dot.acos())
+}
+
+/// `vec` without its component along the normalized `axis`.
+fn reject(vec: Vec3, axis: Vec3) -> Vec3 {
+    vec - axis * vec.dot(axis)
+}
+
+/// The angle from `from` to `to` about `axis`, or zero if either is along the axis.
+fn signed_angle(from: Vec3, to: Vec3, axis: Vec3) -> f32 {
+    if from.length() < 0.0001 || to.length() < 0.0001 {
+        return 0.0;
+    }
+    axis.dot(from.cross(to)).atan2(from.dot(to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FREE: ShoulderLimits = ShoulderLimits {
+        yaw: (-PI, PI),
+        pitch: (-PI, PI),
+        roll: (-PI, PI),
+    };
+
+    /// A right arm, reaching out along +x and bending forward toward -z.
+    fn chain(limits: ShoulderLimits) -> ArmChain {
+        ArmChain {
+            shoulder: Vec3::new(0.2, 1.4, 0.0),
+            upper: Vec3::new(0.3, 0.0, 0.0),
+            lower: Vec3::new(0.25, 0.0, 0.0),
+            hinge: 1.0,
+            limits,
+        }
+    }
+
+    fn pole() -> Vec3 {
+        Vec3::new(0.0, -1.0, 0.5).normalize()
+    }
+
+    fn wrist(chain: &ArmChain, target: Vec3) -> Vec3 {
+        let (shoulder, elbow) = chain.solve(target, pole());
+        assert!(shoulder.is_finite() && elbow.is_finite());
+        chain.wrist(shoulder, elbow)
+    }
+
+    #[test]
+    fn reaches_a_reachable_target() {
+        let chain = chain(FREE);
+        let target = chain.shoulder + Vec3::new(0.25, -0.1, -0.3);
+        assert!(wrist(&chain, target).distance(target) < 0.001);
+    }
+
+    #[test]
+    fn stretches_toward_an_unreachable_target() {
+        let chain = chain(FREE);
+        let direction = Vec3::new(0.3, 0.2, -1.0).normalize();
+        let wrist = wrist(&chain, chain.shoulder + direction * 5.0);
+        let reach = wrist - chain.shoulder;
+        assert!((reach.length() - 0.55).abs() < 0.01);
+        assert!(reach.normalize().dot(direction) > 0.999);
+    }
+
+    #[test]
+    fn folds_up_at_zero_distance() {
+        let chain = chain(FREE);
+        let folded = chain.upper + Quat::from_rotation_y(MAX_ELBOW_BEND) * chain.lower;
+        let wrist = wrist(&chain, chain.shoulder);
+        assert!((wrist.distance(chain.shoulder) - folded.length()).abs() < 0.001);
+    }
+
+    #[test]
+    fn turns_opposite_vectors_around() {
+        for from in [Vec3::X, Vec3::Y, Vec3::Z, -Vec3::X] {
+            let rotation = rotation_arc(from, -from);
+            assert!(rotation.is_finite());
+            assert!((rotation * from).distance(-from) < 0.0001);
+        }
+        // Straight behind the shoulder, against the arm at rest.
+        let chain = chain(FREE);
+        let target = chain.shoulder - Vec3::X * 0.5;
+        assert!(wrist(&chain, target).distance(target) < 0.001);
+    }
+
+    #[test]
+    fn round_trips_yaw_pitch_and_roll() {
+        let (yaw, pitch, roll) = to_ypr(Quat::from_rotation_ypr(0.7, -0.4, 0.3));
+        assert!((yaw - 0.7).abs() < 0.0001);
+        assert!((pitch + 0.4).abs() < 0.0001);
+        assert!((roll - 0.3).abs() < 0.0001);
+    }
+
+    #[test]
+    fn holds_the_shoulder_within_its_limits() {
+        let limits = ShoulderLimits {
+            yaw: (0.0, PI / 4.0),
+            pitch: (-PI / 8.0, PI / 8.0),
+            roll: (-PI / 6.0, PI / 6.0),
+        };
+        let chain = chain(limits);
+        // High up and behind, where a free shoulder would turn far past the limits.
+        let target = chain.shoulder + Vec3::new(-0.1, 0.4, 0.3);
+        let (free, _) = chain.solve_free(target, pole());
+        assert!(limits.clamp(free).dot(free).abs() < 0.99);
+        let (shoulder, elbow) = chain.solve(target, pole());
+        let (yaw, pitch, roll) = to_ypr(shoulder);
+        assert!((-0.0001..=PI / 4.0 + 0.0001).contains(&yaw));
+        assert!(pitch.abs() <= PI / 8.0 + 0.0001);
+        assert!(roll.abs() <= PI / 6.0 + 0.0001);
+        let bend = signed_angle(chain.lower, elbow * chain.lower, Vec3::Y);
+        assert!((-0.0001..=MAX_ELBOW_BEND + 0.0001).contains(&bend));
+        // The elbow still makes up for what the shoulder can't turn.
+        let held = chain.wrist(shoulder, Quat::IDENTITY);
+        assert!(chain.wrist(shoulder, elbow).distance(target) <= held.distance(target) + 0.0001);
+    }
+
+    #[test]
+    fn keeps_a_solution_within_the_limits() {
+        let limits = ShoulderLimits {
+            yaw: (-PI / 2.0, PI / 2.0),
+            ..FREE
+        };
+        let chain = chain(limits);
+        let target = chain.shoulder + Vec3::new(0.3, -0.1, -0.3);
+        assert!(wrist(&chain, target).distance(target) < 0.001);
+    }
+}
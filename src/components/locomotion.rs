@@ -0,0 +1,94 @@
+use std::{f32::consts::FRAC_PI_2, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use super::player::Player;
+
+/// How far a player may move from where they spawned, sideways and backward.
+pub const STANCE_RANGE: f32 = 0.5;
+/// How far a player may move toward the partner.
+pub const MAX_APPROACH: f32 = 0.15;
+/// How far a player may turn away from the partner, in radians.
+pub const MAX_FACING: f32 = FRAC_PI_2;
+/// Meters and radians per second.
+pub const WALK_SPEED: f32 = 0.4;
+pub const TURN_SPEED: f32 = 1.2;
+/// A foot steps once it is this far from where it would stand.
+pub const STEP_DISTANCE: f32 = 0.1;
+pub const STEP_SECONDS: f32 = 0.25;
+pub const STEP_HEIGHT: f32 = 0.05;
+/// How strongly the hip is pulled over the stance.
+pub const HIP_STIFFNESS: f32 = 20.0;
+pub const HIP_DAMPING: f32 = 4.0;
+/// The ground that anchors a ragdoll lies this far below its feet.
+const GROUND_DEPTH: f32 = 0.5;
+
+/// Where a player stands on the floor and which way they face, relative to where they spawned.
+pub struct Stance<T> {
+    /// Where the player spawned.
+    pub origin: Transform,
+    /// Offset from the origin on the floor in the frame of the origin, with z in y.
+    offset: Vec2,
+    /// Rotation about y from the origin; zero faces the partner.
+    facing: f32,
+    data: PhantomData<T>,
+}
+
+impl<T: Player> Stance<T> {
+    pub fn new(origin: Transform) -> Self {
+        Self {
+            origin,
+            offset: Vec2::ZERO,
+            facing: 0.0,
+            data: Default::default(),
+        }
+    }
+
+    pub fn offset(&self) -> Vec2 {
+        self.offset
+    }
+
+    pub fn facing(&self) -> f32 {
+        self.facing
+    }
+
+    /// Sets the stance, keeping it in the play area. Players face -z at the origin.
+    pub fn set(&mut self, offset: Vec2, facing: f32) {
+        let approach = (-offset.y).max(-STANCE_RANGE).min(MAX_APPROACH);
+        self.offset = Vec2::new(offset.x.max(-STANCE_RANGE).min(STANCE_RANGE), -approach);
+        self.facing = facing.max(-MAX_FACING).min(MAX_FACING);
+    }
+
+    /// Walks `forward` and `sideways` relative to the facing, and turns left, for `seconds`.
+    pub fn walk(&mut self, forward: f32, sideways: f32, turn: f32, seconds: f32) {
+        let facing = self.facing + turn * TURN_SPEED * seconds;
+        let direction = Quat::from_rotation_y(facing) * Vec3::new(sideways, 0.0, -forward);
+        let step = direction * WALK_SPEED * seconds;
+        self.set(self.offset + Vec2::new(step.x, step.z), facing);
+    }
+
+    /// The point on the floor the player stands on, facing the partner at rest.
+    pub fn transform(&self) -> Transform {
+        self.origin
+            * Transform::from_translation(Vec3::new(self.offset.x, 0.0, self.offset.y))
+                .with_rotation(Quat::from_rotation_y(self.facing))
+    }
+}
+
+/// The ground body of a player standing at `floor`.
+pub fn ground(floor: &Transform) -> Transform {
+    Transform {
+        translation: floor.translation - Vec3::Y * GROUND_DEPTH,
+        ..*floor
+    }
+}
+
+/// A foot anchor that steps to follow the stance.
+pub struct FootPlant {
+    /// Where the foot stands, relative to the ground.
+    pub rest: Vec3,
+    /// Where the foot is planted.
+    pub planted: Vec3,
+    /// Where the ongoing step started, and how far it is from 0 to 1.
+    pub step: Option<(Vec3, f32)>,
+}
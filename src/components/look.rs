@@ -0,0 +1,102 @@
+use std::{f32::consts::PI, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use super::{body::part::BodyPartId, player::Player};
+
+/// Anatomical limits of turning the head, in radians.
+pub const MAX_YAW: f32 = PI * 7.0 / 18.0;
+pub const MAX_PITCH_UP: f32 = PI / 6.0;
+pub const MAX_PITCH_DOWN: f32 = PI * 2.0 / 9.0;
+/// Radians per second.
+pub const LOOK_SPEED: f32 = 2.5;
+/// The head turns to its left and tilts onto the partner's shoulder while hugging.
+pub const REST_YAW: f32 = PI / 3.0;
+pub const REST_PITCH: f32 = -PI / 12.0;
+pub const REST_ROLL: f32 = PI / 12.0;
+/// How much of the turn the neck takes; the head takes the rest.
+const NECK_SHARE: f32 = 0.4;
+
+/// The head turning toward the partner, layered on top of the pose.
+pub struct HeadLook<T> {
+    pub enabled: bool,
+    /// Yaw to the left, pitch up and roll to the left, in radians.
+    rotation: Vec3,
+    data: PhantomData<T>,
+}
+
+impl<T> Default for HeadLook<T> {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rotation: Vec3::ZERO,
+            data: Default::default(),
+        }
+    }
+}
+
+impl<T: Player> HeadLook<T> {
+    pub fn rotation(&self) -> Vec3 {
+        self.rotation
+    }
+
+    /// Turns toward `target` by at most `max` radians on each axis.
+    pub fn turn_toward(&mut self, target: Vec3, max: f32) {
+        let step = (target - self.rotation)
+            .max(-Vec3::splat(max))
+            .min(Vec3::splat(max));
+        self.rotation += step;
+    }
+
+    /// Yaw and pitch that look along `direction`, given in the frame of the chest.
+    /// Bodies face -z in their own frame.
+    pub fn rotation_toward(direction: Vec3) -> Vec3 {
+        let yaw = (-direction.x).atan2(-direction.z);
+        let horizontal = Vec2::new(direction.x, direction.z).length();
+        let pitch = direction.y.atan2(horizontal);
+        Self::clamped(Vec3::new(yaw, pitch, 0.0))
+    }
+
+    pub fn resting() -> Vec3 {
+        Vec3::new(REST_YAW, REST_PITCH, REST_ROLL)
+    }
+
+    /// Yaw and pitch of the override input, given from -1 to 1.
+    pub fn rotation_of_input(input: Vec2) -> Vec3 {
+        let pitch = if input.y > 0.0 {
+            input.y * MAX_PITCH_UP
+        } else {
+            input.y * MAX_PITCH_DOWN
+        };
+        Self::clamped(Vec3::new(-input.x * MAX_YAW, pitch, 0.0))
+    }
+
+    pub fn clamped(rotation: Vec3) -> Vec3 {
+        Vec3::new(
+            rotation.x.max(-MAX_YAW).min(MAX_YAW),
+            rotation.y.max(-MAX_PITCH_DOWN).min(MAX_PITCH_UP),
+            rotation.z.max(-MAX_PITCH_DOWN).min(MAX_PITCH_DOWN),
+        )
+    }
+
+    /// Rotations of the chest and the neck, which the motors of the neck and the head follow.
+    pub fn layer(&self) -> [(BodyPartId, Quat); 2] {
+        if !self.enabled {
+            return [
+                (BodyPartId::Chest, Quat::IDENTITY),
+                (BodyPartId::Neck, Quat::IDENTITY),
+            ];
+        }
+        let rotation = |share: f32| {
+            Quat::from_rotation_ypr(
+                self.rotation.x * share,
+                self.rotation.y * share,
+                self.rotation.z * share,
+            )
+        };
+        [
+            (BodyPartId::Chest, rotation(NECK_SHARE)),
+            (BodyPartId::Neck, rotation(1.0 - NECK_SHARE)),
+        ]
+    }
+}
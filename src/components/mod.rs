@@ -1,8 +1,25 @@
+pub mod assist;
+pub mod avatar;
 pub mod body;
 pub mod player;
 pub mod physics;
+pub mod pose;
 pub mod control;
+pub mod grab;
+pub mod hug;
+pub mod idle;
+pub mod ik;
+pub mod locomotion;
+pub mod look;
+pub mod name_policy;
 pub mod networking;
 pub mod state;
+pub mod tuning;
 pub mod ui;
 pub mod ragdoll;
+pub mod replay;
+pub mod rig;
+pub mod rounded_box;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod room;
+pub mod skeleton;
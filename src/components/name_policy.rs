@@ -0,0 +1,133 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Names are drawn large above the heads, so keep them short.
+pub const MAX_NAME_GRAPHEMES: usize = 12;
+const MASK: char = '*';
+
+/// Rules applied to every displayed player name, ours and the partner's.
+pub struct NamePolicy {
+    pub max_graphemes: usize,
+    /// Normalized, lowercased words that are masked out of names.
+    pub blocked_words: Vec<String>,
+}
+
+impl Default for NamePolicy {
+    fn default() -> Self {
+        Self {
+            max_graphemes: MAX_NAME_GRAPHEMES,
+            blocked_words: Vec::new(),
+        }
+    }
+}
+
+impl NamePolicy {
+    /// Reads a word filter list, one word per line, normalized like names so that full-width or
+    /// capitalized entries still match.
+    pub fn with_blocked_words(mut self, list: &str) -> Self {
+        self.blocked_words = list
+            .lines()
+            .map(|word| normalize(word).to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        self
+    }
+
+    pub fn sanitize(&self, name: &str) -> String {
+        let mut name: String = normalize(name)
+            .graphemes(true)
+            .take(self.max_graphemes)
+            .collect();
+        for word in self.blocked_words.iter() {
+            name = mask_word(&name, word);
+        }
+        name
+    }
+}
+
+/// Folds compatibility characters like full-width letters, drops forbidden characters and
+/// collapses whitespace.
+fn normalize(text: &str) -> String {
+    let normalized: String = text.nfkc().filter(|c| !is_forbidden(*c)).collect();
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Control characters, bidi overrides and invisible characters that break the layout.
+fn is_forbidden(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{061C}'
+                | '\u{200B}'
+                | '\u{200E}'
+                | '\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{2069}'
+                | '\u{FEFF}'
+        )
+}
+
+fn mask_word(name: &str, word: &str) -> String {
+    let word: Vec<char> = word.chars().collect();
+    if word.is_empty() {
+        return name.into();
+    }
+    let mut chars: Vec<char> = name.chars().collect();
+    let mut i = 0;
+    while i + word.len() <= chars.len() {
+        let window = chars[i..i + word.len()]
+            .iter()
+            .flat_map(|c| c.to_lowercase());
+        if window.eq(word.iter().copied()) {
+            for c in chars[i..i + word.len()].iter_mut() {
+                *c = MASK;
+            }
+            i += word.len();
+        } else {
+            i += 1;
+        }
+    }
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_compatibility_characters() {
+        let policy = NamePolicy::default();
+        assert_eq!(policy.sanitize("ｈｕｇ"), "hug");
+        assert_eq!(policy.sanitize("ﾊｸﾞ"), "ハグ");
+    }
+
+    #[test]
+    fn strips_control_and_bidi_characters() {
+        let policy = NamePolicy::default();
+        assert_eq!(policy.sanitize("a\u{202E}b\u{200B}c\u{7}"), "abc");
+        assert_eq!(policy.sanitize(" a \t\n b "), "a b");
+    }
+
+    #[test]
+    fn truncates_by_graphemes() {
+        let policy = NamePolicy::default();
+        let flags = "🇯🇵".repeat(MAX_NAME_GRAPHEMES + 1);
+        let name = policy.sanitize(&flags);
+        assert_eq!(name.graphemes(true).count(), MAX_NAME_GRAPHEMES);
+        assert_eq!(name, "🇯🇵".repeat(MAX_NAME_GRAPHEMES));
+    }
+
+    #[test]
+    fn masks_blocked_words_in_any_case() {
+        let policy = NamePolicy::default().with_blocked_words("bad\n\n");
+        assert_eq!(policy.sanitize("xBaDy bad"), "x***y ***");
+    }
+
+    #[test]
+    fn normalizes_blocked_words() {
+        let policy = NamePolicy::default().with_blocked_words(" ＢＡＤ \nWorse\u{200B}");
+        assert_eq!(policy.blocked_words, vec!["bad", "worse"]);
+        assert_eq!(policy.sanitize("ｂａｄ worse"), "*** *****");
+    }
+}
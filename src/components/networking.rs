@@ -1,9 +1,13 @@
-use std::{marker::PhantomData, time::Duration};
+use std::marker::PhantomData;
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::player::Player;
+use super::{
+    avatar::Avatar,
+    body::{part::BodyPartId, Pose},
+    player::Player,
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -18,10 +22,20 @@ pub enum HugCommand {
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum HugEvent {
-    Joined { is_primary: bool },
-    RoomCreated { key: String },
+    Joined {
+        is_primary: bool,
+        /// The room server simulates both players and pushes `Payload::State`.
+        #[serde(default)]
+        authoritative: bool,
+    },
+    RoomCreated {
+        key: String,
+    },
     NotFound,
-    Push { payload: Payload },
+    Left,
+    Push {
+        payload: Payload,
+    },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -29,6 +43,17 @@ pub enum Payload {
     HandControl {
         left: Vec2,
         right: Vec2,
+        /// Left grip in x, right grip in y.
+        #[serde(default)]
+        grip: Vec2,
+        #[serde(default)]
+        torso: Vec3,
+        #[serde(default)]
+        look: Option<Vec2>,
+        #[serde(default)]
+        reach: Option<Vec2>,
+        #[serde(default)]
+        assist: f32,
     },
     Sync {
         player1_head: Vec3,
@@ -40,7 +65,51 @@ pub enum Payload {
         player1_hand_right: Vec3,
         player2_hand_right: Vec3,
     },
+    /// Every part of both players as the room server simulates them, sent like `Sync`.
+    State {
+        player1: Vec<PartState>,
+        player2: Vec<PartState>,
+    },
     Name(String),
+    /// A base pose of the sender and how long to blend into it.
+    Pose {
+        pose: Pose,
+        seconds: f32,
+    },
+    Avatar(Avatar),
+    /// The parts of the receiver held by the sender's hands.
+    Grab {
+        left: Option<BodyPartId>,
+        right: Option<BodyPartId>,
+    },
+    /// Where the sender stands, as seen by the sender.
+    Stance {
+        offset: Vec2,
+        facing: f32,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PartState {
+    pub part: BodyPartId,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl PartState {
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_rotation(self.rotation)
+    }
+
+    /// The same part in another scene, e.g. through `partner_to_players`.
+    pub fn mapped(&self, to: &Transform) -> Self {
+        let transform = to.mul_transform(self.transform());
+        Self {
+            part: self.part,
+            translation: transform.translation,
+            rotation: transform.rotation,
+        }
+    }
 }
 
 pub struct Receiver(pub Vec<HugEvent>);
@@ -56,7 +125,79 @@ pub enum IsPrimary {
     No,
 }
 
+/// Who decides where bodies are. The state of a room server is applied as is, while syncs of
+/// a primary peer are validated and eased into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authority {
+    Peer,
+    Server,
+}
+
 #[derive(Default)]
 pub struct PlayerName<P: Player>(pub String, PhantomData<P>);
 
-pub struct ElapsedTime(pub Duration);
+/// Command line options of native builds, e.g. `hug --host` or `hug --join 192.168.0.2`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct LanConfig {
+    pub host: bool,
+    pub join: Option<String>,
+    /// Address of a room server; rooms are then joined by key instead of address.
+    pub server: Option<String>,
+    pub port: u16,
+    pub name: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LanConfig {
+    pub fn from_args() -> Self {
+        let mut config = Self {
+            host: false,
+            join: None,
+            server: None,
+            port: crate::adapters::lan::DEFAULT_PORT,
+            name: None,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" => config.host = true,
+                "--join" => config.join = args.next(),
+                "--server" => config.server = args.next(),
+                "--name" => config.name = args.next(),
+                "--port" => {
+                    if let Some(port) = args.next().and_then(|port| port.parse().ok()) {
+                        config.port = port;
+                    }
+                }
+                _ => warn!("unknown argument: {}", arg),
+            }
+        }
+        config
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Violation {
+    NonFinite,
+    OutOfRange,
+    OutOfBounds,
+    Teleport,
+    NameTooLong,
+    Malformed,
+}
+
+/// Implausible payloads received from the partner.
+/// `strikes` decays over time, so only persistent offenders get disconnected.
+#[derive(Default)]
+pub struct PeerViolations {
+    pub total: u32,
+    pub strikes: f32,
+}
+
+impl PeerViolations {
+    pub fn record(&mut self, violation: Violation) {
+        warn!("invalid payload from the partner: {:?}", violation);
+        self.total += 1;
+        self.strikes += 1.0;
+    }
+}
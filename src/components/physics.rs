@@ -1,4 +1,6 @@
-use std::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+
+use super::{body::part::BodyPartId, player::PlayerId};
 
 bitflags::bitflags! {
     pub struct CollisionTag: u32 {
@@ -14,15 +16,51 @@ impl Into<u32> for CollisionTag {
     }
 }
 
-pub struct Joint<A, B>(PhantomData<(A, B)>);
-
-impl<A, B> Default for Joint<A, B> {
-    fn default() -> Self {
-        Self(Default::default())
-    }
+/// The joint between two body parts.
+pub struct Joint {
+    pub parent: BodyPartId,
+    pub child: BodyPartId,
+    /// The motor follows the rotation of `parent` in `PlayerBody::relative`.
+    pub driven: bool,
 }
 
+/// The static body that a ragdoll is anchored to, and the anchoring joints.
+pub struct GroundAnchor;
+
+/// The motor of a joint as the skeleton defines it, before `MotorTuning`.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct JointMotorParams {
     pub stiffness: f32,
     pub damping: f32,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactPhase {
+    Started,
+    Stopped,
+}
+
+/// Two body parts started or stopped touching.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyContact {
+    pub player_a: PlayerId,
+    pub part_a: BodyPartId,
+    pub player_b: PlayerId,
+    pub part_b: BodyPartId,
+    /// Total normal impulse of the contact points, zero once stopped.
+    pub impulse: f32,
+    pub phase: ContactPhase,
+}
+
+impl BodyContact {
+    /// The part of `player` and the part it touches, if `player` is involved.
+    pub fn part_of(&self, player: PlayerId) -> Option<(BodyPartId, BodyPartId)> {
+        if self.player_a == player {
+            Some((self.part_a, self.part_b))
+        } else if self.player_b == player {
+            Some((self.part_b, self.part_a))
+        } else {
+            None
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use bevy::ecs::component::Component;
+use std::{f32::consts::PI, marker::PhantomData};
+
+use bevy::{ecs::component::Component, prelude::*};
 
 use super::physics::CollisionTag;
 
@@ -10,18 +12,151 @@ pub struct Player2;
 
 pub struct NameText;
 
+/// Tells the players apart at runtime, e.g. in events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerId {
+    Player1,
+    Player2,
+}
+
 pub trait Player: Component + Default {
     fn get_collision_tag() -> CollisionTag;
+    fn id() -> PlayerId;
+    /// Player1 stands at +z and player2 stands across from them, turned around.
+    fn default_placement() -> (Vec3, f32);
 }
 
 impl Player for Player1 {
     fn get_collision_tag() -> CollisionTag {
         CollisionTag::PLAYER1
     }
+
+    fn id() -> PlayerId {
+        PlayerId::Player1
+    }
+
+    fn default_placement() -> (Vec3, f32) {
+        (Vec3::new(0.0, 0.0, 0.2), 0.0)
+    }
 }
 
 impl Player for Player2 {
     fn get_collision_tag() -> CollisionTag {
         CollisionTag::PLAYER2
     }
+
+    fn id() -> PlayerId {
+        PlayerId::Player2
+    }
+
+    fn default_placement() -> (Vec3, f32) {
+        (Vec3::new(0.0, 0.0, -0.2), PI)
+    }
+}
+
+/// Where a player spawns on the floor and which way they face. Bodies face -z in their own
+/// frame, so a yaw of zero faces -z.
+pub struct Placement<T> {
+    pub translation: Vec3,
+    /// Rotation about y, in radians.
+    pub yaw: f32,
+    data: PhantomData<T>,
+}
+
+impl<T: Player> Default for Placement<T> {
+    fn default() -> Self {
+        let (translation, yaw) = T::default_placement();
+        Self::new(translation, yaw)
+    }
+}
+
+impl<T> Placement<T> {
+    pub fn new(translation: Vec3, yaw: f32) -> Self {
+        Self {
+            translation,
+            yaw,
+            data: Default::default(),
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_rotation(Quat::from_rotation_y(self.yaw))
+    }
+}
+
+/// Maps points of the partner's scene into ours, as `(for player1, for player2)`.
+/// The partner stands at player1's place in their scene and at player2's place in ours, and the
+/// other way around for us, so each body keeps where it is relative to its own placement.
+pub fn partner_to_players(
+    player1: &Placement<Player1>,
+    player2: &Placement<Player2>,
+) -> (Mat4, Mat4) {
+    let partner_to_player2 =
+        player2.transform().compute_matrix() * player1.transform().compute_matrix().inverse();
+    (partner_to_player2.inverse(), partner_to_player2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-4, "{} != {}", a, b);
+    }
+
+    /// A point of the sender's player1 and one of its player2, sent as is and mapped by the
+    /// receiver, then sent back and mapped by the original sender.
+    fn round_trip(player1: Placement<Player1>, player2: Placement<Player2>) {
+        let (to_player1, to_player2) = partner_to_players(&player1, &player2);
+        let sender = player1.transform().mul_vec3(Vec3::new(0.1, 1.4, -0.05));
+        let receiver = player2.transform().mul_vec3(Vec3::new(-0.2, 1.0, 0.1));
+
+        // The receiver swaps the players, so the sender becomes its player2.
+        let sender_there = to_player2.transform_point3(sender);
+        let receiver_there = to_player1.transform_point3(receiver);
+        // Both bodies keep their place relative to their own placement.
+        let local = |place: Transform, point: Vec3| {
+            place.compute_matrix().inverse().transform_point3(point)
+        };
+        assert_near(
+            local(player2.transform(), sender_there),
+            local(player1.transform(), sender),
+        );
+        assert_near(
+            local(player1.transform(), receiver_there),
+            local(player2.transform(), receiver),
+        );
+
+        // Sent back, the receiver's player2 is the original sender again.
+        assert_near(to_player1.transform_point3(sender_there), sender);
+        assert_near(to_player2.transform_point3(receiver_there), receiver);
+    }
+
+    #[test]
+    fn round_trips_with_default_placements() {
+        round_trip(Placement::default(), Placement::default());
+    }
+
+    #[test]
+    fn round_trips_with_custom_placements() {
+        round_trip(
+            Placement::new(Vec3::new(0.3, 0.0, 0.5), 0.4),
+            Placement::new(Vec3::new(-0.1, 0.0, -0.6), 2.5),
+        );
+    }
+
+    #[test]
+    fn default_placements_face_each_other() {
+        let (to_player1, to_player2) =
+            partner_to_players(&Placement::default(), &Placement::default());
+        let point = Vec3::new(0.1, 1.0, 0.3);
+        assert_near(
+            to_player2.transform_point3(point),
+            Vec3::new(-0.1, 1.0, -0.3),
+        );
+        assert_near(
+            to_player1.transform_point3(point),
+            Vec3::new(-0.1, 1.0, -0.3),
+        );
+    }
 }
@@ -0,0 +1,118 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::body::Pose;
+
+pub const DEFAULT_BLEND_SECONDS: f32 = 0.5;
+pub const MAX_BLEND_SECONDS: f32 = 5.0;
+
+/// Named poses captured from the local player, e.g. "arms open" or "shy".
+#[derive(Default, Serialize, Deserialize)]
+pub struct PoseLibrary {
+    pub poses: BTreeMap<String, Pose>,
+}
+
+impl PoseLibrary {
+    #[cfg(not(target_arch = "wasm32"))]
+    const PATH: &'static str = "poses.json";
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::PATH)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        if let Err(err) = std::fs::write(Self::PATH, serde_json::to_string(self).unwrap()) {
+            error!("failed to save poses: {}", err);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        storage
+            .get_item("poses")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        let _ = storage.set_item("poses", &serde_json::to_string(self).unwrap());
+    }
+}
+
+pub enum PoseCommand {
+    /// Captures the current pose of the local player.
+    Save {
+        name: String,
+    },
+    /// Blends into a saved pose, or "rest".
+    Apply {
+        name: String,
+        seconds: f32,
+    },
+    Delete {
+        name: String,
+    },
+}
+
+/// The base pose of a player, blending from one pose to another.
+/// `HandControl` is layered on top of it.
+pub struct PoseBlend<T> {
+    pub rest: Pose,
+    from: Pose,
+    to: Pose,
+    seconds: f32,
+    elapsed: f32,
+    data: PhantomData<T>,
+}
+
+impl<T> PoseBlend<T> {
+    pub fn new(pose: Pose) -> Self {
+        Self {
+            rest: pose.clone(),
+            from: pose.clone(),
+            to: pose,
+            seconds: 0.0,
+            elapsed: 0.0,
+            data: Default::default(),
+        }
+    }
+
+    /// Starts blending from wherever the current blend is.
+    pub fn blend_to(&mut self, pose: Pose, seconds: f32) {
+        self.from = self.current();
+        self.to = pose;
+        self.seconds = seconds.max(0.0).min(MAX_BLEND_SECONDS);
+        self.elapsed = 0.0;
+    }
+
+    pub fn is_blending(&self) -> bool {
+        self.elapsed < self.seconds
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.elapsed = (self.elapsed + delta).min(self.seconds);
+    }
+
+    pub fn current(&self) -> Pose {
+        if !self.is_blending() {
+            return self.to.clone();
+        }
+        let t = self.elapsed / self.seconds;
+        // ease in and out
+        self.from.lerp(&self.to, t * t * (3.0 - 2.0 * t))
+    }
+}
@@ -0,0 +1,349 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+
+pub const REPLAY_VERSION: u8 = 1;
+pub const REPLAY_RATE: u8 = 10;
+pub const MAX_REPLAY_FRAMES: usize = 30 * REPLAY_RATE as usize;
+const MAX_NAME_BYTES: usize = 48;
+/// Hand control axes are quantized to 6 bits.
+const LEVELS: u8 = 0x3f;
+/// Bytes above this mark a run of unchanged frames instead of a delta.
+const RUN: u8 = LEVELS;
+const MAX_RUN: u8 = u8::MAX - RUN;
+
+/// Hand controls of both players quantized to 6 bits per axis.
+/// Order: player1 left x/y, player1 right x/y, player2 left x/y, player2 right x/y.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayFrame(pub [u8; 8]);
+
+impl ReplayFrame {
+    pub fn new(player1: (Vec2, Vec2), player2: (Vec2, Vec2)) -> Self {
+        let (l1, r1) = player1;
+        let (l2, r2) = player2;
+        Self([
+            quantize(l1.x),
+            quantize(l1.y),
+            quantize(r1.x),
+            quantize(r1.y),
+            quantize(l2.x),
+            quantize(l2.y),
+            quantize(r2.x),
+            quantize(r2.y),
+        ])
+    }
+
+    pub fn player1(&self) -> (Vec2, Vec2) {
+        self.hands(0)
+    }
+
+    pub fn player2(&self) -> (Vec2, Vec2) {
+        self.hands(4)
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut frame = *self;
+        for (value, target) in frame.0.iter_mut().zip(other.0.iter()) {
+            *value = (*value as f32 + (*target as f32 - *value as f32) * t).round() as u8;
+        }
+        frame
+    }
+
+    fn hands(&self, offset: usize) -> (Vec2, Vec2) {
+        let v = &self.0[offset..offset + 4];
+        (
+            Vec2::new(dequantize(v[0]), dequantize(v[1])),
+            Vec2::new(dequantize(v[2]), dequantize(v[3])),
+        )
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Replay {
+    pub player1_name: String,
+    pub player2_name: String,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Base64,
+    Version(u8),
+    Truncated,
+    Name,
+}
+
+impl Replay {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.frames.len() as f32 / REPLAY_RATE as f32)
+    }
+
+    /// Encodes the replay into a URL-safe string.
+    ///
+    /// Each axis is stored as a separate stream of 6-bit deltas, one byte each,
+    /// and runs of unchanged values are collapsed into a single byte, so a
+    /// still pose costs almost nothing.
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![REPLAY_VERSION];
+        write_name(&mut bytes, &self.player1_name);
+        write_name(&mut bytes, &self.player2_name);
+        bytes.extend_from_slice(&(self.frames.len() as u16).to_le_bytes());
+        for axis in 0..8 {
+            let mut previous = 0u8;
+            let mut zeros = 0;
+            for frame in self.frames.iter() {
+                let value = frame.0[axis];
+                let delta = value.wrapping_sub(previous) & LEVELS;
+                previous = value;
+                if delta == 0 {
+                    zeros += 1;
+                    if zeros == MAX_RUN {
+                        bytes.push(RUN + zeros);
+                        zeros = 0;
+                    }
+                    continue;
+                }
+                if zeros > 0 {
+                    bytes.push(RUN + zeros);
+                    zeros = 0;
+                }
+                bytes.push(delta);
+            }
+            if zeros > 0 {
+                bytes.push(RUN + zeros);
+            }
+        }
+        base64url_encode(&bytes)
+    }
+
+    pub fn decode(text: &str) -> Result<Self, ReplayError> {
+        let bytes = base64url_decode(text).ok_or(ReplayError::Base64)?;
+        let mut reader = Reader {
+            bytes: &bytes,
+            pos: 0,
+        };
+        let version = reader.byte()?;
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::Version(version));
+        }
+        let player1_name = reader.name()?;
+        let player2_name = reader.name()?;
+        let len = u16::from_le_bytes([reader.byte()?, reader.byte()?]) as usize;
+        if len > MAX_REPLAY_FRAMES {
+            return Err(ReplayError::Truncated);
+        }
+        let mut frames = vec![ReplayFrame::default(); len];
+        for axis in 0..8 {
+            let mut value = 0u8;
+            let mut index = 0;
+            while index < len {
+                let token = reader.byte()?;
+                if token > RUN {
+                    let run = (token - RUN) as usize;
+                    if index + run > len {
+                        return Err(ReplayError::Truncated);
+                    }
+                    for frame in frames[index..index + run].iter_mut() {
+                        frame.0[axis] = value;
+                    }
+                    index += run;
+                } else {
+                    value = value.wrapping_add(token) & LEVELS;
+                    frames[index].0[axis] = value;
+                    index += 1;
+                }
+            }
+        }
+        Ok(Self {
+            player1_name,
+            player2_name,
+            frames,
+        })
+    }
+}
+
+pub struct ReplayRecorder {
+    pub timer: Timer,
+    pub frames: VecDeque<ReplayFrame>,
+}
+
+impl Default for ReplayRecorder {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(1.0 / REPLAY_RATE as f32, true),
+            frames: VecDeque::with_capacity(MAX_REPLAY_FRAMES),
+        }
+    }
+}
+
+impl ReplayRecorder {
+    pub fn push(&mut self, frame: ReplayFrame) {
+        if self.frames.len() == MAX_REPLAY_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+}
+
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub elapsed: Duration,
+}
+
+fn quantize(value: f32) -> u8 {
+    (value.max(0.0).min(1.0) * LEVELS as f32).round() as u8
+}
+
+fn dequantize(value: u8) -> f32 {
+    value as f32 / LEVELS as f32
+}
+
+fn write_name(bytes: &mut Vec<u8>, name: &str) {
+    let mut end = name.len().min(MAX_NAME_BYTES);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    bytes.push(end as u8);
+    bytes.extend_from_slice(&name.as_bytes()[..end]);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        let byte = *self.bytes.get(self.pos).ok_or(ReplayError::Truncated)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn name(&mut self) -> Result<String, ReplayError> {
+        let len = self.byte()? as usize;
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or(ReplayError::Truncated)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ReplayError::Name)
+    }
+}
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn base64url_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity((bytes.len() * 4 + 2) / 3);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - i * 8));
+        for i in 0..chunk.len() + 1 {
+            text.push(BASE64URL[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+        }
+    }
+    text
+}
+
+fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64URL.iter().position(|b| b == c)? as u32;
+            n |= value << (18 - i * 6);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - i * 8)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(frames: Vec<ReplayFrame>) -> Replay {
+        Replay {
+            player1_name: "はぐ".into(),
+            player2_name: "hug".into(),
+            frames,
+        }
+    }
+
+    fn assert_round_trip(replay: &Replay) {
+        let decoded = Replay::decode(&replay.encode()).unwrap();
+        assert_eq!(decoded.player1_name, replay.player1_name);
+        assert_eq!(decoded.player2_name, replay.player2_name);
+        assert!(decoded.frames == replay.frames);
+    }
+
+    #[test]
+    fn round_trips() {
+        let frames = (0..MAX_REPLAY_FRAMES)
+            .map(|i| {
+                let t = i as f32 / MAX_REPLAY_FRAMES as f32;
+                ReplayFrame::new(
+                    (Vec2::new(t, 1.0 - t), Vec2::new(0.5, t * t)),
+                    (Vec2::new((t * 20.0).sin().abs(), 0.0), Vec2::ONE),
+                )
+            })
+            .collect();
+        assert_round_trip(&replay(frames));
+        assert_round_trip(&replay(Vec::new()));
+    }
+
+    #[test]
+    fn round_trips_runs_at_the_boundaries() {
+        let still = ReplayFrame([LEVELS; 8]);
+        for len in [
+            MAX_RUN as usize - 1,
+            MAX_RUN as usize,
+            MAX_RUN as usize + 1,
+            MAX_RUN as usize * 2,
+        ]
+        .iter()
+        {
+            // The first frame is a delta of `LEVELS`, which must not read as a run.
+            assert_round_trip(&replay(vec![still; *len]));
+            assert_round_trip(&replay(vec![ReplayFrame::default(); *len]));
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let text = replay(vec![ReplayFrame([1, 2, 3, 4, 5, 6, 7, 8]); 20]).encode();
+        for end in 0..text.len() {
+            assert!(Replay::decode(&text[..end]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for text in ["!!!!", "A", "AAAAA", "////", "\u{3042}"].iter() {
+            assert!(Replay::decode(text).is_err());
+        }
+        assert!(base64url_decode("A").is_none());
+        assert!(base64url_decode("AB=C").is_none());
+        // Every byte string decodes to an error or a replay, never a panic.
+        for seed in 0..2000u32 {
+            let bytes: Vec<u8> = (0..(seed % 40))
+                .map(|i| (seed.wrapping_mul(2_654_435_761).wrapping_add(i * 97) >> 7) as u8)
+                .collect();
+            let _ = Replay::decode(&base64url_encode(&bytes));
+        }
+    }
+
+    #[test]
+    fn base64url_round_trips() {
+        for len in 0..16 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 11) as u8).collect();
+            assert_eq!(base64url_decode(&base64url_encode(&bytes)), Some(bytes));
+        }
+    }
+}
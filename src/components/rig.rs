@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::body::part::BodyPartId;
+
+/// A glTF character rendered instead of the boxes, posed by the ragdoll.
+/// A part drives the bone running from its parent to it, e.g. `ForearmLeft` the upper arm.
+#[derive(Deserialize)]
+pub struct RigConfig {
+    /// Asset path of the scene, e.g. "character.glb#Scene0".
+    pub scene: String,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Names of the nodes of the bones, and the parts that drive them.
+    pub bones: BTreeMap<String, BodyPartId>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl RigConfig {
+    /// Reads `assets/rig.json`; players are boxes without it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Option<Self> {
+        let json = std::fs::read_to_string("assets/rig.json").ok()?;
+        serde_json::from_str(&json)
+            .map_err(|err| error!("invalid rig, using boxes: {}", err))
+            .ok()
+    }
+
+    /// Reads the rig stored in localStorage under "rig".
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Option<Self> {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        let json = storage.get_item("rig").ok().flatten()?;
+        serde_json::from_str(&json)
+            .map_err(|err| error!("invalid rig, using boxes: {}", err))
+            .ok()
+    }
+}
+
+/// The entity the scene of a player is spawned under.
+pub struct RigRoot;
+
+/// Marks a `RigRoot` whose bones are found.
+pub struct RigBound;
+
+pub struct RigBone {
+    pub part: BodyPartId,
+    /// Rotation of the bone relative to its part at rest.
+    pub rest: Quat,
+    pub scale: Vec3,
+}
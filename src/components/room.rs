@@ -0,0 +1,63 @@
+use std::sync::mpsc;
+
+use bevy::prelude::*;
+use parking_lot::Mutex;
+
+use super::{
+    avatar::Avatar,
+    body::{part::BodyPartId, Pose},
+    networking::Payload,
+};
+
+/// Which client a message of a room is from or for.
+/// The first seat simulates as `Player1`, the second as `Player2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seat {
+    First,
+    Second,
+}
+
+pub enum RoomInput {
+    HandControl {
+        seat: Seat,
+        left: Vec2,
+        right: Vec2,
+        grip: Vec2,
+        torso: Vec3,
+        look: Option<Vec2>,
+        reach: Option<Vec2>,
+        assist: f32,
+    },
+    Pose {
+        seat: Seat,
+        pose: Pose,
+        seconds: f32,
+    },
+    Avatar {
+        seat: Seat,
+        avatar: Avatar,
+    },
+    Grab {
+        seat: Seat,
+        left: Option<BodyPartId>,
+        right: Option<BodyPartId>,
+    },
+    Stance {
+        seat: Seat,
+        offset: Vec2,
+        facing: f32,
+    },
+}
+
+pub struct RoomOutput {
+    pub seat: Seat,
+    pub payload: Payload,
+}
+
+/// Channels between the lobby and the headless app simulating a room.
+pub struct RoomLink {
+    pub input: Mutex<mpsc::Receiver<RoomInput>>,
+    pub output: Mutex<mpsc::Sender<RoomOutput>>,
+}
+
+pub struct BroadcastTimer(pub Timer);
@@ -0,0 +1,87 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, pipeline::PrimitiveTopology},
+};
+
+/// A box with rounded edges; with `radius` half its thickness it is a capsule.
+pub struct RoundedBox {
+    pub size: Vec3,
+    pub radius: f32,
+    /// Vertices across each rounded edge.
+    pub segments: usize,
+}
+
+impl RoundedBox {
+    pub fn new(size: Vec3, radius: f32) -> Self {
+        Self {
+            size,
+            radius: radius.min(size.min_element() / 2.0),
+            segments: 6,
+        }
+    }
+}
+
+impl From<RoundedBox> for Mesh {
+    fn from(shape: RoundedBox) -> Self {
+        let half = shape.size / 2.0;
+        let inner = half - Vec3::splat(shape.radius);
+        // Coordinates along each axis, dense on the rounded edges.
+        let samples = |axis: usize| -> Vec<f32> {
+            let (h, r, n) = (half[axis], shape.radius, shape.segments);
+            let start = (0..=n).map(|i| -h + r * i as f32 / n as f32);
+            let end = (0..=n).map(|i| h - r + r * i as f32 / n as f32);
+            start.chain(end).collect()
+        };
+
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+        let mut uvs: Vec<[f32; 2]> = Vec::new();
+        let mut indices = Vec::new();
+        // The normal axis of each face, and two tangent axes with `u × v` along the normal.
+        for (normal, u, v) in [(0, 1, 2), (1, 2, 0), (2, 0, 1)] {
+            for sign in [1.0, -1.0] {
+                let (us, vs) = if sign > 0.0 { (u, v) } else { (v, u) };
+                let (u_samples, v_samples) = (samples(us), samples(vs));
+                let mut face = Vec3::ZERO;
+                face[normal] = sign;
+                let first = positions.len() as u32;
+                for (j, v_value) in v_samples.iter().enumerate() {
+                    for (i, u_value) in u_samples.iter().enumerate() {
+                        let mut point = Vec3::ZERO;
+                        point[normal] = half[normal] * sign;
+                        point[us] = *u_value;
+                        point[vs] = *v_value;
+                        let core = point.max(-inner).min(inner);
+                        let offset = point - core;
+                        let direction = if offset.length_squared() > 0.0 {
+                            offset.normalize()
+                        } else {
+                            face
+                        };
+                        positions.push((core + direction * shape.radius).into());
+                        normals.push(direction.into());
+                        uvs.push([
+                            i as f32 / (u_samples.len() - 1) as f32,
+                            j as f32 / (v_samples.len() - 1) as f32,
+                        ]);
+                    }
+                }
+                let width = u_samples.len() as u32;
+                for j in 0..v_samples.len() as u32 - 1 {
+                    for i in 0..width - 1 {
+                        let a = first + j * width + i;
+                        let (b, c, d) = (a + 1, a + width + 1, a + width);
+                        indices.extend_from_slice(&[a, b, c, a, c, d]);
+                    }
+                }
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::body::part::BodyPartId;
+
+/// Used when `assets/skeleton.json` is missing or invalid.
+const DEFAULT_SKELETON: &str = include_str!("../../assets/skeleton.json");
+
+/// Proportions, shapes and joints of the ragdolls.
+#[derive(Deserialize, Clone)]
+pub struct Skeleton {
+    /// Meters per unit of offsets and shape sizes.
+    pub ratio: f32,
+    /// Parents are listed before their children.
+    pub parts: Vec<PartDef>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PartDef {
+    pub name: BodyPartId,
+    #[serde(default)]
+    pub parent: Option<BodyPartId>,
+    /// Rest offset from the parent, with the body facing -z.
+    pub offset: [f32; 3],
+    pub shape: Shape,
+    /// Radius of the rounded edges of cubes and bones.
+    #[serde(default)]
+    pub rounding: f32,
+    #[serde(default)]
+    pub mesh: bool,
+    #[serde(default)]
+    pub density: Option<f32>,
+    #[serde(default)]
+    pub joint: JointDef,
+    #[serde(default)]
+    pub balloon: Option<f32>,
+    #[serde(default)]
+    pub anchor: Option<Anchor>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum Shape {
+    /// A box centered on the part.
+    Cube { size: [f32; 3] },
+    /// A box from the parent to the part, `width` and `thickness` across it.
+    Bone { width: f32, thickness: f32 },
+    /// A ball between the parent and the part.
+    Ball { radius: f32 },
+    /// A capsule from the parent to the part.
+    Capsule { radius: f32 },
+}
+
+/// The joint between a part and its parent.
+#[derive(Deserialize, Clone, Copy)]
+pub struct JointDef {
+    pub stiffness: f32,
+    pub damping: f32,
+    /// The motor follows the rotation of the parent in the pose, like elbows and knees.
+    #[serde(default)]
+    pub driven: bool,
+}
+
+impl Default for JointDef {
+    fn default() -> Self {
+        Self {
+            stiffness: 1.5,
+            damping: 1.5,
+            driven: false,
+        }
+    }
+}
+
+/// Joints to the ground that keep a ragdoll standing.
+#[derive(Deserialize, Clone, Copy)]
+pub enum Anchor {
+    Rotation,
+    Position,
+}
+
+#[derive(Debug)]
+pub enum SkeletonError {
+    Parse(serde_json::Error),
+    DuplicatePart(BodyPartId),
+    MissingPart(BodyPartId),
+    /// The parent is listed after the part.
+    InvalidParent(BodyPartId),
+    /// Only the first part has no parent.
+    InvalidRoot(BodyPartId),
+}
+
+impl Skeleton {
+    pub fn parse(json: &str) -> Result<Self, SkeletonError> {
+        let skeleton: Self = serde_json::from_str(json).map_err(SkeletonError::Parse)?;
+        let mut seen = HashSet::new();
+        for (index, part) in skeleton.parts.iter().enumerate() {
+            match part.parent {
+                None if index != 0 => return Err(SkeletonError::InvalidRoot(part.name)),
+                Some(_) if index == 0 => return Err(SkeletonError::InvalidRoot(part.name)),
+                Some(parent) if !seen.contains(&parent) => {
+                    return Err(SkeletonError::InvalidParent(part.name))
+                }
+                _ => {}
+            }
+            if !seen.insert(part.name) {
+                return Err(SkeletonError::DuplicatePart(part.name));
+            }
+        }
+        if let Some(missing) = BodyPartId::ALL
+            .iter()
+            .find(|part| !part.is_optional() && !seen.contains(*part))
+        {
+            return Err(SkeletonError::MissingPart(*missing));
+        }
+        Ok(skeleton)
+    }
+
+    /// Reads `assets/skeleton.json`, so proportions can be tweaked without recompiling.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        match std::fs::read_to_string("assets/skeleton.json") {
+            Ok(json) => Self::parse(&json).unwrap_or_else(|err| {
+                error!("invalid skeleton, using the default: {:?}", err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// The embedded skeleton, unless one is stored in localStorage under "skeleton".
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        match storage.get_item("skeleton") {
+            Ok(Some(json)) if !json.is_empty() => Self::parse(&json).unwrap_or_else(|err| {
+                error!("invalid skeleton, using the default: {:?}", err);
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn offset(&self, part: &PartDef) -> Vec3 {
+        Vec3::from(part.offset) * self.ratio
+    }
+}
+
+impl Default for Skeleton {
+    fn default() -> Self {
+        Self::parse(DEFAULT_SKELETON).expect("the embedded skeleton is invalid")
+    }
+}
@@ -5,4 +5,5 @@ pub enum AppState {
     MatchingByKey,
     CreatingRoom,
     Connected,
+    Replaying,
 }
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{body::part::BodyPartId, physics::JointMotorParams};
+
+/// How stiff the ragdolls feel overall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MotorPreset {
+    Floppy,
+    Normal,
+    Firm,
+}
+
+impl Default for MotorPreset {
+    fn default() -> Self {
+        MotorPreset::Normal
+    }
+}
+
+impl MotorPreset {
+    /// Multiplies the stiffness and damping of every joint.
+    pub fn scale(self) -> f32 {
+        match self {
+            MotorPreset::Floppy => 0.4,
+            MotorPreset::Normal => 1.0,
+            MotorPreset::Firm => 2.5,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            MotorPreset::Floppy => MotorPreset::Normal,
+            MotorPreset::Normal => MotorPreset::Firm,
+            MotorPreset::Firm => MotorPreset::Floppy,
+        }
+    }
+}
+
+/// Stiffness and damping of the joint motors, applied live on top of the skeleton.
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotorTuning {
+    #[serde(default)]
+    pub preset: MotorPreset,
+    /// Replace the skeleton's motor of the joint between a part and its parent.
+    #[serde(default)]
+    pub joints: HashMap<BodyPartId, JointMotorParams>,
+}
+
+#[derive(Debug)]
+pub enum TuningError {
+    Parse(serde_json::Error),
+    /// The stiffness or damping is negative or not finite.
+    InvalidMotor(BodyPartId),
+}
+
+impl MotorTuning {
+    pub fn parse(json: &str) -> Result<Self, TuningError> {
+        let tuning: Self = serde_json::from_str(json).map_err(TuningError::Parse)?;
+        let is_valid = |value: f32| value.is_finite() && value >= 0.0;
+        if let Some((part, _)) = tuning
+            .joints
+            .iter()
+            .find(|(_, params)| !is_valid(params.stiffness) || !is_valid(params.damping))
+        {
+            return Err(TuningError::InvalidMotor(*part));
+        }
+        Ok(tuning)
+    }
+
+    /// The motor of the joint between `part` and its parent, given the skeleton's.
+    pub fn params(&self, part: BodyPartId, skeleton: &JointMotorParams) -> JointMotorParams {
+        let params = self.joints.get(&part).unwrap_or(skeleton);
+        let scale = self.preset.scale();
+        JointMotorParams {
+            stiffness: params.stiffness * scale,
+            damping: params.damping * scale,
+        }
+    }
+
+    /// Reads `assets/motor_tuning.json`; every joint keeps the skeleton's motor without it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        match std::fs::read_to_string("assets/motor_tuning.json") {
+            Ok(json) => Self::parse(&json).unwrap_or_else(|err| {
+                error!("invalid motor tuning, using the skeleton: {:?}", err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Reads the tuning stored in localStorage under "motor_tuning".
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        match storage.get_item("motor_tuning") {
+            Ok(Some(json)) if !json.is_empty() => Self::parse(&json).unwrap_or_else(|err| {
+                error!("invalid motor tuning, using the skeleton: {:?}", err);
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+}
@@ -18,6 +18,14 @@ pub struct Messages {
     pub copied: &'static str,
     pub share: &'static str,
     pub share_match: &'static str,
+    /// Appended to `share_match` once a hug was scored.
+    pub share_best_score: &'static str,
+    pub hug_score: &'static str,
     pub room_link: &'static str,
     pub tags: &'static str,
+    pub replaying: &'static str,
+    pub replay_invalid: &'static str,
+    pub disconnected: &'static str,
+    pub left: &'static str,
+    pub hosting: &'static str,
 }
@@ -0,0 +1,21 @@
+pub mod adapters;
+pub mod components;
+pub mod plugins;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+pub mod systems;
+
+use bevy::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum HugSystems {
+    InputSystem,
+    MoveSystem,
+    ProgagateTransformSystem,
+    ContactSystem,
+    TrackContactsSystem,
+    DetectHugSystem,
+    ScoreHugSystem,
+}
+
+pub const WIDTH: f32 = 300.0;
@@ -1,33 +1,33 @@
-mod adapters;
-mod components;
-mod plugins;
-mod systems;
-
 use bevy::prelude::*;
 
 #[cfg(not(target_arch = "wasm32"))]
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_rapier3d::prelude::*;
 use js_sys::Function;
-use plugins::body::BodyPlugin;
 use wasm_bindgen::prelude::*;
 
-use components::{
-    state::AppState,
-    ui::{Device, Messages},
-};
+#[cfg(not(target_arch = "wasm32"))]
+use hug::plugins::lan::LanPlugin;
 #[cfg(target_arch = "wasm32")]
-use plugins::{networking::NetworkPlugin, ui::UiPlugin};
-use systems::{scene::setup, setup_player::setup_player};
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
-pub enum HugSystems {
-    InputSystem,
-    MoveSystem,
-    ProgagateTransformSystem,
-}
-
-pub const WIDTH: f32 = 300.0;
+use hug::{
+    components::{avatar::Avatar, pose::PoseCommand},
+    plugins::{replay::ReplayPlugin, ui::UiPlugin},
+    systems, WIDTH,
+};
+use hug::{
+    components::{
+        state::AppState,
+        ui::{Device, Messages},
+    },
+    plugins::{
+        avatar::AvatarPlugin, body::BodyPlugin, control::ControlPlugin, hug::HugPlugin,
+        networking::NetworkPlugin, pose::PosePlugin, rig::RigPlugin,
+    },
+    systems::{
+        scene::{setup, setup_physics},
+        setup_player::setup_player,
+    },
+};
 
 #[bevy_main]
 fn main() {
@@ -51,8 +51,15 @@ fn main() {
         copied: "Copied to clipboard",
         share: "The Hug game is amazing! Try it out!",
         share_match: "With {name}, we've hugged for {minute} minutes and {second} seconds.",
+        share_best_score: " Our best hug scored {score}.",
+        hug_score: "Hug score: {score}",
         room_link: "Hug with Me?",
         tags: "#metaverse #thehuggame",
+        replaying: "Replaying a hug",
+        replay_invalid: "The replay link is broken.",
+        disconnected: "Disconnected because of invalid data from the partner.",
+        left: "Your partner left.",
+        hosting: "Waiting for your partner at {address}",
     };
     #[cfg(target_arch = "wasm32")]
     {
@@ -85,10 +92,16 @@ fn main() {
                     room_notfound: "ルームに入れませんでした",
                     copied: "コピーしました！",
                     share: "ハグゲームは最高です！みんなでハグしよう！",
-                    share_match:
-                        "{name}さんと{minute}分{second}秒ハグしました",
+                    share_match: "{name}さんと{minute}分{second}秒ハグしました。",
+                    share_best_score: "ベストハグは{score}点",
+                    hug_score: "ハグスコア: {score}点",
                     room_link: "わたしとハグしませんか？",
                     tags: "#メタバース #ハグゲーム",
+                    replaying: "ハグを再生中です",
+                    replay_invalid: "リプレイのリンクが壊れています",
+                    disconnected: "相手から不正なデータが届いたため切断しました",
+                    left: "相手が退出しました",
+                    hosting: "{address} で相手を待っています",
                 };
             }
         }
@@ -98,18 +111,26 @@ fn main() {
         .insert_resource(device)
         .insert_resource(messages)
         .add_plugins(DefaultPlugins)
+        .add_plugin(ControlPlugin)
+        .add_plugin(AvatarPlugin)
         .add_plugin(BodyPlugin)
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         // .add_plugin(RapierRenderPlugin)
+        .add_startup_system(setup_physics.system())
         .add_startup_system(setup.system())
         .add_startup_system(setup_player.system())
-        .add_state(AppState::Alone);
+        .add_state(AppState::Alone)
+        .add_plugin(HugPlugin)
+        .add_plugin(NetworkPlugin)
+        .add_plugin(PosePlugin)
+        .add_plugin(RigPlugin);
 
     #[cfg(not(target_arch = "wasm32"))]
-    app.add_plugin(WorldInspectorPlugin::new());
+    app.add_plugin(WorldInspectorPlugin::new())
+        .add_plugin(LanPlugin);
 
     #[cfg(target_arch = "wasm32")]
-    app.add_plugin(NetworkPlugin).add_plugin(UiPlugin);
+    app.add_plugin(UiPlugin).add_plugin(ReplayPlugin);
 
     // bevy_mod_debugdump::print_schedule(&mut app);
 
@@ -151,3 +172,39 @@ pub fn on_click_room() {
 pub fn on_click_share() {
     *systems::networking::SHARE_BUTTON.lock() = true;
 }
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn on_save_pose(name: &str) {
+    systems::pose::POSE_COMMANDS
+        .lock()
+        .push(PoseCommand::Save { name: name.into() });
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn on_apply_pose(name: &str, seconds: f32) {
+    systems::pose::POSE_COMMANDS
+        .lock()
+        .push(PoseCommand::Apply {
+            name: name.into(),
+            seconds,
+        });
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn on_delete_pose(name: &str) {
+    systems::pose::POSE_COMMANDS
+        .lock()
+        .push(PoseCommand::Delete { name: name.into() });
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn on_avatar_change(avatar: &str) {
+    match serde_json::from_str::<Avatar>(avatar) {
+        Ok(avatar) => *systems::avatar::AVATAR.lock() = Some(avatar),
+        Err(err) => warn!("invalid avatar: {}", err),
+    }
+}
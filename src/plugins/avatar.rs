@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        avatar::{Avatar, PlayerAvatar},
+        player::Player1,
+    },
+    systems::avatar::{apply_avatar, avatar_input},
+    HugSystems,
+};
+
+/// The look of the local player, stored locally and sent to the partner.
+pub struct AvatarPlugin;
+
+impl Plugin for AvatarPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(PlayerAvatar::<Player1>::new(Avatar::load()))
+            .add_system(avatar_input.system().label(HugSystems::InputSystem))
+            .add_system(apply_avatar.system().after(HugSystems::InputSystem));
+    }
+}
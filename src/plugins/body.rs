@@ -3,12 +3,26 @@ use bevy_rapier3d::physics::PhysicsSystems;
 
 use crate::{
     components::{
-        body::part::*,
-        player::{Player1, Player2},
+        assist::ReachAssist,
+        avatar::PlayerAvatar,
+        grab::Grabs,
+        look::HeadLook,
+        physics::BodyContact,
+        player::{Placement, Player1, Player2},
+        skeleton::Skeleton,
+        tuning::MotorTuning,
     },
     systems::{
         active_ragdoll::{baloon_system, hand_baloon_system},
-        control::{angular_spring_system, keyboard_input, move_system, move_system2, touch_input},
+        assist::track_partner_torso,
+        contact::emit_body_contacts,
+        control::{
+            angular_spring_system, animate_idle, move_system, move_system2, tick_pose_blend,
+        },
+        grab::{apply_grabs, break_grabs},
+        locomotion::{move_stance, plant_feet},
+        look::look_at_partner,
+        setup_player::rebuild_body,
     },
     HugSystems,
 };
@@ -17,44 +31,71 @@ pub struct BodyPlugin;
 
 impl Plugin for BodyPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_set(
-            SystemSet::new()
-                .label(HugSystems::InputSystem)
-                .with_system(keyboard_input.system())
-                .with_system(touch_input.system()),
-        )
-        .add_system_set(
-            SystemSet::new()
-                .label(HugSystems::MoveSystem)
-                .after(HugSystems::InputSystem)
-                .with_system(move_system.system())
-                .with_system(move_system2.system()),
-        )
-        .add_system_set(
-            SystemSet::new()
-                .before(PhysicsSystems::StepWorld)
-                .with_system(baloon_system::<Player1, Head>.system())
-                .with_system(baloon_system::<Player1, Hip>.system())
-                .with_system(hand_baloon_system::<Player1>.system())
-                .with_system(baloon_system::<Player2, Head>.system())
-                .with_system(baloon_system::<Player2, Hip>.system())
-                .with_system(hand_baloon_system::<Player2>.system())
-                .with_system(angular_spring_system::<Player1, UpperArmLeft, ForearmLeft>.system())
-                .with_system(angular_spring_system::<Player1, ForearmLeft, HandLeft>.system())
-                .with_system(angular_spring_system::<Player1, UpperArmRight, ForearmRight>.system())
-                .with_system(angular_spring_system::<Player1, ForearmRight, HandRight>.system())
-                .with_system(angular_spring_system::<Player1, ThighLeft, ShinLeft>.system())
-                .with_system(angular_spring_system::<Player1, ShinLeft, FootLeft>.system())
-                .with_system(angular_spring_system::<Player1, ThighRight, ShinRight>.system())
-                .with_system(angular_spring_system::<Player1, ShinRight, FootRight>.system())
-                .with_system(angular_spring_system::<Player2, UpperArmLeft, ForearmLeft>.system())
-                .with_system(angular_spring_system::<Player2, ForearmLeft, HandLeft>.system())
-                .with_system(angular_spring_system::<Player2, UpperArmRight, ForearmRight>.system())
-                .with_system(angular_spring_system::<Player2, ForearmRight, HandRight>.system())
-                .with_system(angular_spring_system::<Player2, ThighLeft, ShinLeft>.system())
-                .with_system(angular_spring_system::<Player2, ShinLeft, FootLeft>.system())
-                .with_system(angular_spring_system::<Player2, ThighRight, ShinRight>.system())
-                .with_system(angular_spring_system::<Player2, ShinRight, FootRight>.system()),
-        );
+        app.insert_resource(Skeleton::load())
+            .insert_resource(MotorTuning::load())
+            .init_resource::<Placement<Player1>>()
+            .init_resource::<Placement<Player2>>()
+            .init_resource::<PlayerAvatar<Player1>>()
+            .init_resource::<PlayerAvatar<Player2>>()
+            .init_resource::<Grabs<Player1>>()
+            .init_resource::<Grabs<Player2>>()
+            .init_resource::<HeadLook<Player1>>()
+            .init_resource::<HeadLook<Player2>>()
+            .init_resource::<ReachAssist<Player1>>()
+            .init_resource::<ReachAssist<Player2>>()
+            .add_event::<BodyContact>()
+            .add_system(emit_body_contacts.system().label(HugSystems::ContactSystem))
+            .add_system_set(
+                SystemSet::new()
+                    .after(HugSystems::InputSystem)
+                    .with_system(rebuild_body::<Player1>.system())
+                    .with_system(rebuild_body::<Player2>.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .after(HugSystems::InputSystem)
+                    .with_system(apply_grabs::<Player1, Player2>.system())
+                    .with_system(apply_grabs::<Player2, Player1>.system())
+                    .with_system(break_grabs::<Player1>.system())
+                    .with_system(break_grabs::<Player2>.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .after(HugSystems::InputSystem)
+                    .before(PhysicsSystems::StepWorld)
+                    .with_system(move_stance::<Player1>.system())
+                    .with_system(move_stance::<Player2>.system())
+                    .with_system(plant_feet::<Player1>.system())
+                    .with_system(plant_feet::<Player2>.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .label(HugSystems::MoveSystem)
+                    .after(HugSystems::InputSystem)
+                    .with_system(move_system.system())
+                    .with_system(move_system2.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .before(HugSystems::MoveSystem)
+                    .with_system(tick_pose_blend::<Player1>.system())
+                    .with_system(tick_pose_blend::<Player2>.system())
+                    .with_system(animate_idle::<Player1>.system())
+                    .with_system(animate_idle::<Player2>.system())
+                    .with_system(look_at_partner::<Player1, Player2>.system())
+                    .with_system(look_at_partner::<Player2, Player1>.system())
+                    .with_system(track_partner_torso::<Player1, Player2>.system())
+                    .with_system(track_partner_torso::<Player2, Player1>.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .before(PhysicsSystems::StepWorld)
+                    .with_system(baloon_system::<Player1>.system())
+                    .with_system(hand_baloon_system::<Player1>.system())
+                    .with_system(baloon_system::<Player2>.system())
+                    .with_system(hand_baloon_system::<Player2>.system())
+                    .with_system(angular_spring_system::<Player1>.system())
+                    .with_system(angular_spring_system::<Player2>.system()),
+            );
     }
 }
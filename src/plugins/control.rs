@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use crate::{
+    systems::{
+        control::{grip_input, keyboard_input, look_input, torso_input, touch_input},
+        grab::detect_grabs,
+        locomotion::walk_input,
+        tuning::tuning_input,
+    },
+    HugSystems,
+};
+
+/// Local input, left out of headless apps.
+pub struct ControlPlugin;
+
+impl Plugin for ControlPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::new()
+                .label(HugSystems::InputSystem)
+                .with_system(keyboard_input.system())
+                .with_system(touch_input.system())
+                .with_system(grip_input.system())
+                .with_system(torso_input.system())
+                .with_system(look_input.system())
+                .with_system(walk_input.system())
+                .with_system(tuning_input.system()),
+        )
+        .add_system(detect_grabs.system().after(HugSystems::InputSystem));
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::hug::{HugEnded, HugScore, HugStarted, HugStats, PlayerContacts},
+    systems::hug::{detect_hug, log_hugs, score_hug, track_contacts},
+    HugSystems,
+};
+
+/// Tells when the players hug each other, and for how long.
+pub struct HugPlugin;
+
+impl Plugin for HugPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<PlayerContacts>()
+            .init_resource::<HugStats>()
+            .init_resource::<HugScore>()
+            .add_event::<HugStarted>()
+            .add_event::<HugEnded>()
+            .add_system(
+                track_contacts
+                    .system()
+                    .label(HugSystems::TrackContactsSystem)
+                    .after(HugSystems::ContactSystem),
+            )
+            .add_system(
+                detect_hug
+                    .system()
+                    .label(HugSystems::DetectHugSystem)
+                    .after(HugSystems::TrackContactsSystem),
+            )
+            .add_system(
+                score_hug
+                    .system()
+                    .label(HugSystems::ScoreHugSystem)
+                    .after(HugSystems::DetectHugSystem),
+            )
+            .add_system(log_hugs.system().after(HugSystems::ScoreHugSystem));
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use crate::{
+    adapters::{discovery::LanDiscovery, lan::LanTransport},
+    components::{
+        body::part::*,
+        networking::{LanConfig, PlayerName},
+        player::{Player1, Player2},
+        ui::{Alert, Message},
+    },
+    systems::{
+        name::{insert_name, update_name, update_name_position},
+        networking::lan,
+    },
+    HugSystems,
+};
+
+pub struct LanPlugin;
+
+impl Plugin for LanPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let config = LanConfig::from_args();
+        let mut transport = LanTransport::new(config.port);
+        if let Some(server) = &config.server {
+            transport = transport.with_server(server);
+        }
+        app.insert_resource(transport)
+            .insert_resource(LanDiscovery::new())
+            .insert_resource(config)
+            .insert_resource(Alert("".into()))
+            .insert_resource(Message("".into()))
+            .insert_resource(PlayerName::<Player1>::default())
+            .insert_resource(PlayerName::<Player2>::default())
+            .add_startup_system(lan::start_lan.system())
+            .add_system_to_stage(CoreStage::PreUpdate, lan::sender.system())
+            .add_system_to_stage(CoreStage::PreUpdate, lan::receiver.system())
+            .add_system(lan::log_messages.system())
+            .add_system(lan::discover.system())
+            .add_system(lan::match_random.system())
+            .add_system(
+                lan::discovery_input
+                    .system()
+                    .before(HugSystems::InputSystem),
+            )
+            .add_startup_system(lan::insert_discovery_text.system())
+            .add_system(lan::update_discovery_text.system())
+            .add_startup_system(insert_name::<Player1, true>.system())
+            .add_startup_system(insert_name::<Player2, false>.system())
+            .add_system(update_name::<Player1>.system())
+            .add_system(update_name::<Player2>.system())
+            .add_system(update_name_position::<Player1, UpperArmRight, true>.system())
+            .add_system(update_name_position::<Player2, UpperArmRight, false>.system());
+    }
+}
@@ -1,5 +1,15 @@
-#[cfg(target_arch = "wasm32")]
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lan;
 pub mod networking;
+pub mod pose;
 #[cfg(target_arch = "wasm32")]
 pub mod ui;
+pub mod avatar;
 pub mod body;
+pub mod control;
+pub mod hug;
+#[cfg(target_arch = "wasm32")]
+pub mod replay;
+pub mod rig;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod room;
@@ -1,18 +1,20 @@
-use std::time::Duration;
-
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::{RigidBodyType, RigidBodyTypeComponent};
 
 use crate::{
     components::{
-        networking::{ElapsedTime, IsPrimary, PushTimer, Receiver, Sender, SyncTimer, WaitTimer},
-        player::Player2,
+        name_policy::NamePolicy,
+        networking::{
+            Authority, IsPrimary, PeerViolations, PushTimer, Receiver, Sender, SyncTimer, WaitTimer,
+        },
+        player::{Player1, Player2},
         state::AppState,
         ui::Message,
     },
     systems::networking::{
-        back_to_alone, elapse_time, event_handlers, handle_event::handle_events, join_room, sync,
-        transport, update_name, when_connect,
+        back_to_alone, disconnect_offender, event_handlers,
+        handle_event::{apply_state, handle_events},
+        sync, update_name, when_connect,
     },
     HugSystems,
 };
@@ -26,12 +28,23 @@ impl Plugin for NetworkPlugin {
             .insert_resource(PushTimer(Timer::from_seconds(1.0 / 20.0, true)))
             .insert_resource(SyncTimer(Timer::from_seconds(1.0, true)))
             .insert_resource(WaitTimer(Timer::from_seconds(30.0, false)))
-            .insert_resource(ElapsedTime(Duration::default()))
             .insert_resource(IsPrimary::No)
-            .add_startup_system(join_room.system())
-            .add_system_to_stage(CoreStage::PreUpdate, transport::sender.system())
-            .add_system_to_stage(CoreStage::PreUpdate, transport::receiver.system())
+            .insert_resource(Authority::Peer)
+            .insert_resource(PeerViolations::default())
+            .insert_resource(NamePolicy::default())
             .add_system(handle_events.system().before(HugSystems::MoveSystem))
+            .add_system(
+                apply_state::<Player1>
+                    .system()
+                    .before(HugSystems::MoveSystem)
+                    .with_run_criteria(when_connect.system()),
+            )
+            .add_system(
+                apply_state::<Player2>
+                    .system()
+                    .before(HugSystems::MoveSystem)
+                    .with_run_criteria(when_connect.system()),
+            )
             .add_system_set(
                 SystemSet::on_enter(AppState::MatchingRandom)
                     .with_system(event_handlers::random_matching.system()),
@@ -60,6 +73,22 @@ impl Plugin for NetworkPlugin {
                     .system()
                     .with_run_criteria(when_connect.system()),
             )
+            .add_system(
+                sync::sync_avatar
+                    .system()
+                    .with_run_criteria(when_connect.system()),
+            )
+            .add_system(
+                sync::sync_grabs
+                    .system()
+                    .with_run_criteria(when_connect.system()),
+            )
+            .add_system(
+                sync::sync_stance
+                    .system()
+                    .after(HugSystems::InputSystem)
+                    .with_run_criteria(when_connect.system()),
+            )
             .add_system(update_name.system())
             .add_system_set(
                 SystemSet::on_exit(AppState::Connected)
@@ -68,11 +97,22 @@ impl Plugin for NetworkPlugin {
             .add_system_set(
                 SystemSet::on_enter(AppState::Alone).with_system(event_handlers::cleanup.system()),
             )
+            .add_system(back_to_alone.system())
             .add_system(
-                elapse_time
+                disconnect_offender
                     .system()
+                    .after(HugSystems::MoveSystem)
                     .with_run_criteria(when_connect.system()),
-            )
-            .add_system(back_to_alone.system());
+            );
+
+        // The transport of native builds is added by `LanPlugin`.
+        #[cfg(target_arch = "wasm32")]
+        {
+            use crate::systems::networking::{join_room, load_name_policy, transport};
+            app.add_startup_system(load_name_policy.system())
+                .add_startup_system(join_room.system())
+                .add_system_to_stage(CoreStage::PreUpdate, transport::sender.system())
+                .add_system_to_stage(CoreStage::PreUpdate, transport::receiver.system());
+        }
     }
 }
@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::pose::PoseLibrary,
+    systems::pose::{handle_pose_commands, pose_input},
+    HugSystems,
+};
+
+/// Named poses of the local player.
+pub struct PosePlugin;
+
+impl Plugin for PosePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(PoseLibrary::load())
+            .add_system(pose_input.system().label(HugSystems::InputSystem))
+            .add_system(
+                handle_pose_commands
+                    .system()
+                    .after(HugSystems::InputSystem)
+                    .before(HugSystems::MoveSystem),
+            );
+    }
+}
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{replay::ReplayRecorder, state::AppState},
+    systems::{
+        networking::when_connect,
+        replay::{load_replay, on_replay_started, play_replay, record_replay, start_recording},
+    },
+    HugSystems,
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(ReplayRecorder::default())
+            .add_startup_system(load_replay.system())
+            .add_system_set(
+                SystemSet::on_enter(AppState::Connected).with_system(start_recording.system()),
+            )
+            .add_system(
+                record_replay
+                    .system()
+                    .after(HugSystems::InputSystem)
+                    .with_run_criteria(when_connect.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Replaying).with_system(on_replay_started.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Replaying)
+                    .after(HugSystems::InputSystem)
+                    .before(HugSystems::MoveSystem)
+                    .with_system(play_replay.system()),
+            );
+    }
+}
@@ -0,0 +1,32 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{
+    components::{
+        player::{Player1, Player2},
+        rig::RigConfig,
+    },
+    systems::rig::{bind_rig, drive_rig, spawn_rig},
+};
+
+/// Renders the players with the glTF character of `RigConfig`, when there is one.
+pub struct RigPlugin;
+
+impl Plugin for RigPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let config = match RigConfig::load() {
+            Some(config) => config,
+            None => return,
+        };
+        app.insert_resource(config)
+            .add_startup_system(spawn_rig.system())
+            .add_system(bind_rig::<Player1>.system())
+            .add_system(bind_rig::<Player2>.system())
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .before(TransformSystem::TransformPropagate)
+                    .with_system(drive_rig::<Player1>.system())
+                    .with_system(drive_rig::<Player2>.system()),
+            );
+    }
+}
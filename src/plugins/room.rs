@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::room::BroadcastTimer,
+    systems::{
+        room::{apply_input, broadcast_state},
+        scene::setup_physics,
+        setup_player::setup_headless_player,
+    },
+    HugSystems,
+};
+
+/// Simulates a room of the server; `RoomLink` must be inserted.
+pub struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(BroadcastTimer(Timer::from_seconds(1.0 / 20.0, true)))
+            .add_startup_system(setup_physics.system())
+            .add_startup_system(setup_headless_player.system())
+            .add_system(apply_input.system().label(HugSystems::InputSystem))
+            .add_system(broadcast_state.system());
+    }
+}
@@ -9,8 +9,12 @@ use crate::{
     },
     systems::{
         name::{insert_name, update_name, update_name_position},
-        ui::{remove_alert, share, update_alert, update_message, update_state_by_button},
+        ui::{
+            alert_hug_score, remove_alert, share, update_alert, update_message,
+            update_state_by_button,
+        },
     },
+    HugSystems,
 };
 
 pub struct UiPlugin;
@@ -32,6 +36,7 @@ impl Plugin for UiPlugin {
             .add_system(update_name::<Player2>.system())
             .add_system(update_name_position::<Player1, UpperArmRight, true>.system())
             .add_system(update_name_position::<Player2, UpperArmRight, false>.system())
-            .add_system(share.system());
+            .add_system(share.system())
+            .add_system(alert_hug_score.system().after(HugSystems::ScoreHugSystem));
     }
 }
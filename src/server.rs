@@ -0,0 +1,428 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt,
+    hash::{BuildHasher, Hasher},
+    net::{SocketAddr, UdpSocket},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use bevy::{app::ScheduleRunnerSettings, log::LogPlugin, prelude::*};
+use bevy_rapier3d::prelude::*;
+use parking_lot::Mutex;
+
+use crate::{
+    adapters::{
+        lan::{bind, recv_packets, Connection, Packet, DEFAULT_PORT},
+        web_socket::{WebSocketConnection, WebSocketListener},
+    },
+    components::{
+        networking::{HugCommand, HugEvent, Payload},
+        room::{RoomInput, RoomLink, RoomOutput, Seat},
+    },
+    plugins::{body::BodyPlugin, room::RoomPlugin},
+};
+
+const TICK: Duration = Duration::from_micros(1_000_000 / 60);
+const KEY_CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const KEY_LENGTH: usize = 6;
+const DEFAULT_WEB_PORT: u16 = 47802;
+
+/// Native clients talk over UDP and web clients over a websocket, on ports of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Peer {
+    Udp(SocketAddr),
+    Web(SocketAddr),
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Peer::Udp(addr) => write!(f, "{}", addr),
+            Peer::Web(addr) => write!(f, "{} (web)", addr),
+        }
+    }
+}
+
+enum Link {
+    Udp(Connection<HugEvent, HugCommand>),
+    Web(WebSocketConnection<HugEvent, HugCommand>),
+}
+
+struct Client {
+    link: Link,
+    room: Option<u32>,
+}
+
+struct Room {
+    seats: [Peer; 2],
+    input: mpsc::Sender<RoomInput>,
+    output: mpsc::Receiver<RoomOutput>,
+}
+
+impl Room {
+    fn seat(&self, addr: Peer) -> Seat {
+        if self.seats[0] == addr {
+            Seat::First
+        } else {
+            Seat::Second
+        }
+    }
+
+    fn partner(&self, addr: Peer) -> Peer {
+        if self.seats[0] == addr {
+            self.seats[1]
+        } else {
+            self.seats[0]
+        }
+    }
+}
+
+/// Pairs up clients and forwards their messages to the room they are in.
+pub struct Lobby {
+    socket: UdpSocket,
+    web: WebSocketListener,
+    clients: HashMap<Peer, Client>,
+    waiting: Option<Peer>,
+    keys: HashMap<String, Peer>,
+    rooms: HashMap<u32, Room>,
+    next_room: u32,
+}
+
+impl Lobby {
+    pub fn new(socket: UdpSocket, web: WebSocketListener) -> Self {
+        Self {
+            socket,
+            web,
+            clients: HashMap::new(),
+            waiting: None,
+            keys: HashMap::new(),
+            rooms: HashMap::new(),
+            next_room: 0,
+        }
+    }
+
+    fn receive(&mut self) {
+        for connection in self.web.accept() {
+            let addr = Peer::Web(connection.addr);
+            info!("{} connected", addr);
+            self.clients.insert(
+                addr,
+                Client {
+                    link: Link::Web(connection),
+                    room: None,
+                },
+            );
+        }
+        for (addr, packet) in recv_packets::<HugCommand>(&self.socket) {
+            let mut commands = Vec::new();
+            let peer = Peer::Udp(addr);
+            let client = self.clients.entry(peer).or_insert_with(|| {
+                info!("{} connected", peer);
+                Client {
+                    link: Link::Udp(Connection::new(addr)),
+                    room: None,
+                }
+            });
+            if let Packet::Bye = packet {
+                commands.push(HugCommand::Leave);
+            }
+            if let Link::Udp(connection) = &mut client.link {
+                connection.receive(&self.socket, packet, &mut commands);
+            }
+            for command in commands {
+                self.handle(peer, command);
+            }
+        }
+        let addrs: Vec<_> = self.clients.keys().copied().collect();
+        for addr in addrs {
+            let mut commands = Vec::new();
+            if let Some(client) = self.clients.get_mut(&addr) {
+                match &mut client.link {
+                    Link::Udp(connection) => connection.drain_unreliable(&mut commands),
+                    Link::Web(connection) => connection.receive(&mut commands),
+                }
+            }
+            for command in commands {
+                self.handle(addr, command);
+            }
+        }
+    }
+
+    fn handle(&mut self, addr: Peer, command: HugCommand) {
+        match command {
+            HugCommand::JoinRandom => {
+                self.leave(addr);
+                match self.waiting.take() {
+                    Some(waiting) if waiting != addr => self.open_room(waiting, addr),
+                    _ => self.waiting = Some(addr),
+                }
+            }
+            HugCommand::CreateRoom => {
+                self.leave(addr);
+                let key = self.new_key();
+                self.keys.insert(key.clone(), addr);
+                self.send(addr, HugEvent::RoomCreated { key });
+            }
+            HugCommand::JoinRoom { key } => {
+                self.leave(addr);
+                match self.keys.remove(&key) {
+                    Some(host) if host != addr && self.clients.contains_key(&host) => {
+                        self.open_room(host, addr)
+                    }
+                    _ => self.send(addr, HugEvent::NotFound),
+                }
+            }
+            HugCommand::Leave => self.leave(addr),
+            HugCommand::Push { payload } => self.push(addr, payload),
+        }
+    }
+
+    fn push(&mut self, addr: Peer, payload: Payload) {
+        let room = match self.clients.get(&addr).and_then(|client| client.room) {
+            Some(id) => &self.rooms[&id],
+            None => return,
+        };
+        let partner = room.partner(addr);
+        match payload {
+            Payload::HandControl {
+                left,
+                right,
+                grip,
+                torso,
+                look,
+                reach,
+                assist,
+            } => {
+                let _ = room.input.send(RoomInput::HandControl {
+                    seat: room.seat(addr),
+                    left,
+                    right,
+                    grip,
+                    torso,
+                    look,
+                    reach,
+                    assist,
+                });
+                // The partner still predicts our hands between states.
+                self.send(partner, HugEvent::Push { payload });
+            }
+            Payload::Pose { ref pose, seconds } => {
+                let _ = room.input.send(RoomInput::Pose {
+                    seat: room.seat(addr),
+                    pose: pose.clone(),
+                    seconds,
+                });
+                self.send(partner, HugEvent::Push { payload });
+            }
+            Payload::Avatar(ref avatar) => {
+                let _ = room.input.send(RoomInput::Avatar {
+                    seat: room.seat(addr),
+                    avatar: avatar.clone(),
+                });
+                self.send(partner, HugEvent::Push { payload });
+            }
+            Payload::Grab { left, right } => {
+                let _ = room.input.send(RoomInput::Grab {
+                    seat: room.seat(addr),
+                    left,
+                    right,
+                });
+                self.send(partner, HugEvent::Push { payload });
+            }
+            Payload::Stance { offset, facing } => {
+                let _ = room.input.send(RoomInput::Stance {
+                    seat: room.seat(addr),
+                    offset,
+                    facing,
+                });
+                self.send(partner, HugEvent::Push { payload });
+            }
+            Payload::Name(_) => self.send(partner, HugEvent::Push { payload }),
+            // Only the room decides where bodies are.
+            Payload::Sync { .. } | Payload::State { .. } => {}
+        }
+    }
+
+    fn open_room(&mut self, first: Peer, second: Peer) {
+        let id = self.next_room;
+        self.next_room += 1;
+        let (input, room_input) = mpsc::channel();
+        let (room_output, output) = mpsc::channel();
+        spawn_room(id, room_input, room_output);
+        self.rooms.insert(
+            id,
+            Room {
+                seats: [first, second],
+                input,
+                output,
+            },
+        );
+        for addr in [first, second] {
+            if let Some(client) = self.clients.get_mut(&addr) {
+                client.room = Some(id);
+            }
+            // Neither client is the physics authority; the room is.
+            self.send(
+                addr,
+                HugEvent::Joined {
+                    is_primary: false,
+                    authoritative: true,
+                },
+            );
+        }
+        info!("room {} opened for {} and {}", id, first, second);
+    }
+
+    fn leave(&mut self, addr: Peer) {
+        if self.waiting == Some(addr) {
+            self.waiting = None;
+        }
+        self.keys.retain(|_, host| *host != addr);
+        let id = match self
+            .clients
+            .get_mut(&addr)
+            .and_then(|client| client.room.take())
+        {
+            Some(id) => id,
+            None => return,
+        };
+        // Dropping the room closes its channels, which stops its app.
+        if let Some(room) = self.rooms.remove(&id) {
+            let partner = room.partner(addr);
+            if let Some(client) = self.clients.get_mut(&partner) {
+                client.room = None;
+            }
+            self.send(partner, HugEvent::Left);
+            info!("room {} closed", id);
+        }
+    }
+
+    fn broadcast(&mut self) {
+        let mut outputs = Vec::new();
+        for room in self.rooms.values() {
+            for RoomOutput { seat, payload } in room.output.try_iter() {
+                let addr = match seat {
+                    Seat::First => room.seats[0],
+                    Seat::Second => room.seats[1],
+                };
+                outputs.push((addr, payload));
+            }
+        }
+        for (addr, payload) in outputs {
+            self.send(addr, HugEvent::Push { payload });
+        }
+    }
+
+    fn expire(&mut self) {
+        for client in self.clients.values_mut() {
+            match &mut client.link {
+                Link::Udp(connection) => connection.resend(&self.socket),
+                Link::Web(connection) => connection.flush(),
+            }
+        }
+        let timed_out: Vec<_> = self
+            .clients
+            .iter()
+            .filter(|(_, client)| match &client.link {
+                Link::Udp(connection) => connection.is_timed_out(),
+                Link::Web(connection) => connection.is_closed(),
+            })
+            .map(|(addr, _)| *addr)
+            .collect();
+        for addr in timed_out {
+            info!("{} timed out", addr);
+            self.leave(addr);
+            self.clients.remove(&addr);
+        }
+    }
+
+    fn send(&mut self, addr: Peer, event: HugEvent) {
+        if let Some(client) = self.clients.get_mut(&addr) {
+            match &mut client.link {
+                Link::Udp(connection) => connection.send(&self.socket, event),
+                Link::Web(connection) => connection.send(event),
+            }
+        }
+    }
+
+    fn new_key(&self) -> String {
+        loop {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u32(self.next_room);
+            let mut bits = hasher.finish();
+            let key: String = (0..KEY_LENGTH)
+                .map(|_| {
+                    let c = KEY_CHARS[(bits % KEY_CHARS.len() as u64) as usize];
+                    bits /= KEY_CHARS.len() as u64;
+                    c as char
+                })
+                .collect();
+            if !self.keys.contains_key(&key) {
+                return key;
+            }
+        }
+    }
+}
+
+pub fn serve(mut lobby: ResMut<Lobby>) {
+    lobby.receive();
+    lobby.broadcast();
+    lobby.expire();
+}
+
+/// Runs the ragdolls of a room in their own headless app until the room is dropped.
+fn spawn_room(id: u32, input: mpsc::Receiver<RoomInput>, output: mpsc::Sender<RoomOutput>) {
+    thread::Builder::new()
+        .name(format!("room-{}", id))
+        .spawn(move || {
+            App::build()
+                .insert_resource(ScheduleRunnerSettings::run_loop(TICK))
+                .insert_resource(RoomLink {
+                    input: Mutex::new(input),
+                    output: Mutex::new(output),
+                })
+                .add_plugins(MinimalPlugins)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+                .add_plugin(BodyPlugin)
+                .add_plugin(RoomPlugin)
+                .run();
+        })
+        .expect("failed to spawn a room thread");
+}
+
+/// Entry point of the `server` binary, e.g. `server --port 47800 --web-port 47802`.
+pub fn run() {
+    let mut port = DEFAULT_PORT;
+    let mut web_port = DEFAULT_WEB_PORT;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().and_then(|port| port.parse().ok());
+        match (arg.as_str(), value) {
+            ("--port", Some(value)) => port = value,
+            ("--web-port", Some(value)) => web_port = value,
+            // Logging starts with the app, so this goes straight to stderr.
+            _ => eprintln!("unknown argument: {}", arg),
+        }
+    }
+    let socket = bind(port).expect("failed to bind the server port");
+    let web = WebSocketListener::bind(web_port).expect("failed to bind the websocket port");
+    App::build()
+        .insert_resource(ScheduleRunnerSettings::run_loop(TICK))
+        .insert_resource(Lobby::new(socket, web))
+        .add_plugins(MinimalPlugins)
+        .add_plugin(LogPlugin)
+        .add_startup_system(listening.system())
+        .add_system(serve.system())
+        .run();
+}
+
+fn listening(lobby: Res<Lobby>) {
+    if let Ok(addr) = lobby.socket.local_addr() {
+        info!("listening on {}", addr);
+    }
+    if let Ok(addr) = lobby.web.local_addr() {
+        info!("listening for web clients on {}", addr);
+    }
+}
@@ -2,25 +2,26 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
 use crate::components::{
-    body::{part::*, BodyPart, PlayerBody},
+    body::{part::*, PlayerBody},
     player::Player,
     ragdoll::BallonForceCoef,
 };
 
-pub fn baloon_system<T: Player, P: BodyPart>(
+pub fn baloon_system<T: Player>(
     body: Res<PlayerBody<T>>,
-    mut head: Query<
+    mut parts: Query<
         (
+            &BodyPartId,
             &mut RigidBodyForcesComponent,
             &RigidBodyPositionComponent,
             &BallonForceCoef,
         ),
-        (With<T>, With<P>),
+        With<T>,
     >,
 ) {
-    for (mut forces, pos, coef) in head.iter_mut() {
+    for (part, mut forces, pos, coef) in parts.iter_mut() {
         let t = pos.position.translation;
-        let diff = body.absolute.get::<P>().translation - Vec3::new(t.x, t.y, t.z);
+        let diff = body.absolute.part(*part).translation - Vec3::new(t.x, t.y, t.z);
         let force = coef.0 * diff;
         forces.force += vector!(force.x, force.y, force.z);
     }
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::components::{
+    assist::ReachAssist,
+    body::part::{Chest, Spine},
+    control::HandControl,
+    player::Player,
+};
+
+/// Meters the partner's torso moves before the arms follow.
+const TORSO_TOLERANCE: f32 = 0.001;
+
+/// Finds the torso of `U` relative to the chest of `T`, while the assist of `T` is on.
+pub fn track_partner_torso<T: Player, U: Player>(
+    control: Res<HandControl<T>>,
+    mut assist: ResMut<ReachAssist<T>>,
+    chest: Query<&RigidBodyPositionComponent, (With<T>, With<Chest>)>,
+    partner_chest: Query<&RigidBodyPositionComponent, (With<U>, With<Chest>)>,
+    partner_spine: Query<&RigidBodyPositionComponent, (With<U>, With<Spine>)>,
+) {
+    let torso = match (
+        chest.single(),
+        partner_chest.single(),
+        partner_spine.single(),
+    ) {
+        (Ok(chest), Ok(partner_chest), Ok(partner_spine)) if control.assist() > 0.0 => {
+            let center = (partner_chest.0.position.translation.vector
+                + partner_spine.0.position.translation.vector)
+                / 2.0;
+            let local = chest.0.position.inverse_transform_point(&center.into());
+            Some(Vec3::new(local.x, local.y, local.z))
+        }
+        _ => None,
+    };
+    // Compared first, so the arms are only posed again when it moves beyond the jitter of the
+    // physics.
+    let moved = match (torso, assist.torso()) {
+        (Some(torso), Some(tracked)) => torso.distance(tracked) > TORSO_TOLERANCE,
+        (torso, tracked) => torso.is_some() != tracked.is_some(),
+    };
+    if moved {
+        assist.set_torso(torso);
+    }
+}
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use parking_lot::Mutex;
+
+use crate::components::{
+    avatar::{Avatar, PlayerAvatar, PALETTE},
+    player::Player1,
+};
+
+lazy_static::lazy_static! {
+    pub static ref AVATAR: Mutex<Option<Avatar>> = Mutex::new(None);
+}
+
+/// `C` cycles through the palette and `Shift+C` through the head shapes.
+pub fn avatar_input(input: Res<Input<KeyCode>>, avatar: Res<PlayerAvatar<Player1>>) {
+    if !input.just_pressed(KeyCode::C) {
+        return;
+    }
+    let mut next = avatar.0.clone();
+    if input.pressed(KeyCode::LShift) || input.pressed(KeyCode::RShift) {
+        next.head = next.head.next();
+    } else {
+        next.color = (next.color + 1) % PALETTE.len() as u8;
+    }
+    *AVATAR.lock() = Some(next);
+}
+
+pub fn apply_avatar(mut avatar: ResMut<PlayerAvatar<Player1>>) {
+    if let Some(next) = AVATAR.lock().take() {
+        let next = next.clamped();
+        next.save();
+        avatar.0 = next;
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::components::{
+    body::part::BodyPartId,
+    physics::{BodyContact, ContactPhase},
+    player::{Player1, Player2, PlayerId},
+};
+
+/// Translates the contact events of Rapier between body parts into `BodyContact`s.
+pub fn emit_body_contacts(
+    mut events: EventReader<ContactEvent>,
+    mut contacts: EventWriter<BodyContact>,
+    narrow_phase: Res<NarrowPhase>,
+    parts: Query<(&BodyPartId, Option<&Player1>), Or<(With<Player1>, With<Player2>)>>,
+) {
+    let body_part = |collider: ColliderHandle| {
+        parts.get(collider.entity()).ok().map(|(part, player1)| {
+            let player = if player1.is_some() {
+                PlayerId::Player1
+            } else {
+                PlayerId::Player2
+            };
+            (player, *part)
+        })
+    };
+    for event in events.iter() {
+        let (collider1, collider2, phase) = match *event {
+            ContactEvent::Started(collider1, collider2) => {
+                (collider1, collider2, ContactPhase::Started)
+            }
+            ContactEvent::Stopped(collider1, collider2) => {
+                (collider1, collider2, ContactPhase::Stopped)
+            }
+        };
+        let ((player_a, part_a), (player_b, part_b)) =
+            match (body_part(collider1), body_part(collider2)) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+        let impulse = match phase {
+            ContactPhase::Started => {
+                narrow_phase
+                    .contact_pair(collider1, collider2)
+                    .map_or(0.0, |pair| {
+                        pair.manifolds
+                            .iter()
+                            .flat_map(|manifold| manifold.points.iter())
+                            .map(|point| point.data.impulse)
+                            .sum()
+                    })
+            }
+            ContactPhase::Stopped => 0.0,
+        };
+        contacts.send(BodyContact {
+            player_a,
+            part_a,
+            player_b,
+            part_b,
+            impulse,
+            phase,
+        });
+    }
+}
@@ -1,15 +1,33 @@
-use bevy::{ecs::component::Component, prelude::*};
+use std::collections::{HashMap, HashSet};
+
+use bevy::{input::touch::Touch, prelude::*};
 use bevy_rapier3d::{physics::JointHandleComponent, prelude::*};
 
-use crate::{
-    components::{
-        body::{part::*, PlayerBody},
-        control::HandControl,
-        physics::{Joint, JointMotorParams},
-        player::{Player, Player1, Player2},
-    },
+use crate::components::{
+    assist::ReachAssist,
+    body::{part::BodyPartId, PlayerBody, Pose},
+    control::HandControl,
+    idle::IdleAnimation,
+    look::HeadLook,
+    physics::{Joint, JointMotorParams},
+    player::{Player, Player1, Player2},
+    pose::PoseBlend,
+    tuning::MotorTuning,
 };
 
+/// Grip per second.
+const GRIP_SPEED: f32 = 4.0;
+/// Lean and twist per second.
+const TORSO_SPEED: f32 = 2.0;
+const LONG_TOUCH_SECONDS: f32 = 0.5;
+/// Part of the screen height, at the bottom, where touches move the torso instead of the arms.
+const TORSO_STRIP: f32 = 0.2;
+/// `F` steps the assist up by this much, then back off.
+const ASSIST_STEP: f32 = 0.25;
+/// Keys that do something else with shift: saving a pose, the head shape and reloading the
+/// tuning.
+const SHIFT_SHORTCUTS: [KeyCode; 3] = [KeyCode::P, KeyCode::C, KeyCode::Y];
+
 pub fn touch_input(
     mut control: ResMut<HandControl<Player1>>,
     touches: Res<Touches>,
@@ -18,7 +36,10 @@ pub fn touch_input(
 ) {
     if let Some(window) = windows.get_primary() {
         let width = window.width() * window.scale_factor() as f32;
-        for touch in touches.iter() {
+        for touch in touches
+            .iter()
+            .filter(|touch| !is_torso_touch(touch, window))
+        {
             let delta = touch.delta();
             let delta = Vec2::new(delta.x / (width / 5.0), delta.y / (width / 2.0));
             if touch.start_position().x < width / 2.0 {
@@ -30,21 +51,54 @@ pub fn touch_input(
     }
 }
 
+/// `E` switches between turning the arms and placing the hands, where holding shift makes
+/// `W`/`S` and `I`/`K` reach forward and back instead of up and down, unless shift is held for
+/// another shortcut. `F` sets how strongly the hands are drawn around the partner.
 pub fn keyboard_input(
     time: Res<Time>,
     mut control: ResMut<HandControl<Player1>>,
     input: Res<Input<KeyCode>>,
 ) {
+    if input.just_pressed(KeyCode::F) {
+        let assist = match control.assist() {
+            assist if assist >= 1.0 => 0.0,
+            assist => assist + ASSIST_STEP,
+        };
+        control.set_assist(assist);
+        info!("reach assist at {:.0}%", control.assist() * 100.0);
+    }
+    if input.just_pressed(KeyCode::E) {
+        let reach = match control.reach() {
+            Some(_) => None,
+            None => Some(Vec2::splat(0.5)),
+        };
+        control.set_reach(reach);
+    }
+    let reaching = control.reach().is_some()
+        && (input.pressed(KeyCode::LShift) || input.pressed(KeyCode::RShift));
+    // The hands hold still while shift is held for another shortcut.
+    if reaching && !SHIFT_SHORTCUTS.iter().any(|key| input.pressed(*key)) {
+        let key_axis = |positive: KeyCode, negative: KeyCode| {
+            input.pressed(positive) as u8 as f32 - input.pressed(negative) as u8 as f32
+        };
+        let reach = Vec2::new(
+            key_axis(KeyCode::W, KeyCode::S),
+            key_axis(KeyCode::I, KeyCode::K),
+        );
+        if reach != Vec2::ZERO {
+            control.add_reach(reach * time.delta_seconds());
+        }
+    }
     if input.pressed(KeyCode::A) {
         control.add_left(Vec2::new(-1.0, 0.0) * time.delta_seconds());
     }
     if input.pressed(KeyCode::D) {
         control.add_left(Vec2::new(1.0, 0.0) * time.delta_seconds());
     }
-    if input.pressed(KeyCode::W) {
+    if !reaching && input.pressed(KeyCode::W) {
         control.add_left(Vec2::new(0.0, 1.0) * time.delta_seconds());
     }
-    if input.pressed(KeyCode::S) {
+    if !reaching && input.pressed(KeyCode::S) {
         control.add_left(Vec2::new(0.0, -1.0) * time.delta_seconds());
     }
     if input.pressed(KeyCode::J) {
@@ -53,52 +107,250 @@ pub fn keyboard_input(
     if input.pressed(KeyCode::L) {
         control.add_right(Vec2::new(1.0, 0.0) * time.delta_seconds());
     }
-    if input.pressed(KeyCode::I) {
+    if !reaching && input.pressed(KeyCode::I) {
         control.add_right(Vec2::new(0.0, 1.0) * time.delta_seconds());
     }
-    if input.pressed(KeyCode::K) {
+    if !reaching && input.pressed(KeyCode::K) {
         control.add_right(Vec2::new(0.0, -1.0) * time.delta_seconds());
     }
 }
 
-pub fn move_system(control: Res<HandControl<Player1>>, mut body: ResMut<PlayerBody<Player1>>) {
-    if control.is_changed() {
-        let (b, a, c) = control.left_sholder();
-        body.relative.get_mut::<UpperArmLeft>().rotation = Quat::from_rotation_ypr(a, b, c);
-        let (b, a, c) = control.right_sholder();
-        body.relative.get_mut::<UpperArmRight>().rotation = Quat::from_rotation_ypr(a, b, c);
-        let (b, a, c) = control.left_elbow();
-        body.relative.get_mut::<ForearmLeft>().rotation = Quat::from_rotation_ypr(a, b, c);
-        let (b, a, c) = control.right_elbow();
-        body.relative.get_mut::<ForearmRight>().rotation = Quat::from_rotation_ypr(a, b, c);
+/// Holding `Q` or `U`, or touching a side of the screen for a while, closes that hand.
+pub fn grip_input(
+    time: Res<Time>,
+    mut control: ResMut<HandControl<Player1>>,
+    input: Res<Input<KeyCode>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut held: Local<HashMap<u64, f32>>,
+) {
+    let delta = time.delta_seconds();
+    let mut target = Vec2::new(
+        input.pressed(KeyCode::Q) as u8 as f32,
+        input.pressed(KeyCode::U) as u8 as f32,
+    );
+    held.retain(|id, _| touches.get_pressed(*id).is_some());
+    if let Some(window) = windows.get_primary() {
+        let width = window.width() * window.scale_factor() as f32;
+        for touch in touches
+            .iter()
+            .filter(|touch| !is_torso_touch(touch, window))
+        {
+            let seconds = held.entry(touch.id()).or_default();
+            *seconds += delta;
+            if *seconds >= LONG_TOUCH_SECONDS {
+                if touch.start_position().x < width / 2.0 {
+                    target.x = 1.0;
+                } else {
+                    target.y = 1.0;
+                }
+            }
+        }
+    }
+    let grip = *control.grip();
+    let max = Vec2::splat(GRIP_SPEED * delta);
+    let step = (target - grip).max(-max).min(max);
+    if step != Vec2::ZERO {
+        control.set_grip(grip + step);
+    }
+}
+
+/// `T`/`G` lean forward and back, `V`/`B` lean left and right and `N`/`M` twist, or the right
+/// stick and the shoulder buttons of a gamepad. On touch screens, dragging from the left of the
+/// bottom strip leans that way and dragging sideways from its right twists. The torso
+/// straightens up once released.
+pub fn torso_input(
+    time: Res<Time>,
+    mut control: ResMut<HandControl<Player1>>,
+    input: Res<Input<KeyCode>>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut gamepads: Local<HashSet<Gamepad>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    for GamepadEvent(gamepad, event) in gamepad_events.iter() {
+        match event {
+            GamepadEventType::Connected => {
+                gamepads.insert(*gamepad);
+            }
+            GamepadEventType::Disconnected => {
+                gamepads.remove(gamepad);
+            }
+            _ => {}
+        }
+    }
+    let key_axis = |positive: KeyCode, negative: KeyCode| {
+        input.pressed(positive) as u8 as f32 - input.pressed(negative) as u8 as f32
+    };
+    let mut target = Vec3::new(
+        key_axis(KeyCode::T, KeyCode::G),
+        key_axis(KeyCode::B, KeyCode::V),
+        key_axis(KeyCode::N, KeyCode::M),
+    );
+    for gamepad in gamepads.iter() {
+        let axis = |axis| axes.get(GamepadAxis(*gamepad, axis)).unwrap_or_default();
+        let button = |button| buttons.pressed(GamepadButton(*gamepad, button)) as u8 as f32;
+        target += Vec3::new(
+            axis(GamepadAxisType::RightStickY),
+            axis(GamepadAxisType::RightStickX),
+            button(GamepadButtonType::LeftTrigger) - button(GamepadButtonType::RightTrigger),
+        );
+    }
+    if let Some(window) = windows.get_primary() {
+        let width = window.width() * window.scale_factor() as f32;
+        for touch in touches.iter().filter(|touch| is_torso_touch(touch, window)) {
+            // Screen y grows downward, so dragging up leans forward.
+            let drag = (touch.position() - touch.start_position()) / (width / 5.0);
+            if touch.start_position().x < width / 2.0 {
+                target += Vec3::new(-drag.y, drag.x, 0.0);
+            } else {
+                target.z -= drag.x;
+            }
+        }
+    }
+    let target = target.max(-Vec3::ONE).min(Vec3::ONE);
+    let torso = *control.torso();
+    let max = Vec3::splat(TORSO_SPEED * time.delta_seconds());
+    let step = (target - torso).max(-max).min(max);
+    if step != Vec3::ZERO {
+        control.set_torso(torso + step);
+    }
+}
+
+fn is_torso_touch(touch: &Touch, window: &Window) -> bool {
+    let height = window.height() * window.scale_factor() as f32;
+    touch.start_position().y > height * (1.0 - TORSO_STRIP)
+}
 
-        body.absolute = body.relative.propagated();
+/// The numpad turns the head, instead of looking at the partner.
+pub fn look_input(mut control: ResMut<HandControl<Player1>>, input: Res<Input<KeyCode>>) {
+    let key_axis = |positive: KeyCode, negative: KeyCode| {
+        input.pressed(positive) as u8 as f32 - input.pressed(negative) as u8 as f32
+    };
+    let look = Vec2::new(
+        key_axis(KeyCode::Numpad6, KeyCode::Numpad4),
+        key_axis(KeyCode::Numpad8, KeyCode::Numpad2),
+    );
+    let look = Some(look).filter(|look| *look != Vec2::ZERO);
+    if look != control.look() {
+        control.set_look(look);
     }
 }
 
-pub fn move_system2(control: Res<HandControl<Player2>>, mut body: ResMut<PlayerBody<Player2>>) {
-    if control.is_changed() {
-        let (b, a, c) = control.left_sholder();
-        body.relative.get_mut::<UpperArmLeft>().rotation = Quat::from_rotation_ypr(a, b, c);
-        let (b, a, c) = control.right_sholder();
-        body.relative.get_mut::<UpperArmRight>().rotation = Quat::from_rotation_ypr(a, b, c);
-        let (b, a, c) = control.left_elbow();
-        body.relative.get_mut::<ForearmLeft>().rotation = Quat::from_rotation_ypr(a, b, c);
-        let (b, a, c) = control.right_elbow();
-        body.relative.get_mut::<ForearmRight>().rotation = Quat::from_rotation_ypr(a, b, c);
+pub fn move_system(
+    control: Res<HandControl<Player1>>,
+    blend: Res<PoseBlend<Player1>>,
+    idle: Res<IdleAnimation<Player1>>,
+    look: Res<HeadLook<Player1>>,
+    assist: Res<ReachAssist<Player1>>,
+    mut shift: Local<Vec3>,
+    mut body: ResMut<PlayerBody<Player1>>,
+) {
+    if control.is_changed()
+        || blend.is_changed()
+        || idle.is_changed()
+        || look.is_changed()
+        || assist.is_changed()
+    {
+        let layer: Vec<_> = control
+            .layer(&body.relative, assist.torso())
+            .iter()
+            .chain(look.layer().iter())
+            .copied()
+            .collect();
+        apply_layers(&mut body, &blend.current(), &idle, &mut shift, &layer);
+    }
+}
 
-        body.absolute = body.relative.propagated();
+pub fn move_system2(
+    control: Res<HandControl<Player2>>,
+    blend: Res<PoseBlend<Player2>>,
+    idle: Res<IdleAnimation<Player2>>,
+    look: Res<HeadLook<Player2>>,
+    assist: Res<ReachAssist<Player2>>,
+    mut shift: Local<Vec3>,
+    mut body: ResMut<PlayerBody<Player2>>,
+) {
+    if control.is_changed()
+        || blend.is_changed()
+        || idle.is_changed()
+        || look.is_changed()
+        || assist.is_changed()
+    {
+        let layer: Vec<_> = control
+            .layer(&body.relative, assist.torso())
+            .iter()
+            .chain(look.layer().iter())
+            .copied()
+            .collect();
+        apply_layers(&mut body, &blend.current(), &idle, &mut shift, &layer);
+    }
+}
+
+/// Sets the rotations of the base pose, then adds the idle motion and the control layers.
+/// The root stays where the player stands, apart from the idle weight shift.
+fn apply_layers<T: Player>(
+    body: &mut PlayerBody<T>,
+    base: &Pose,
+    idle: &IdleAnimation<T>,
+    applied_shift: &mut Vec3,
+    control: &[(BodyPartId, Quat)],
+) {
+    for part in BodyPartId::ALL.iter() {
+        if body.relative.parent(*part).is_some() {
+            body.relative.part_mut(*part).rotation = base.0[part.index()].rotation;
+        }
     }
+    for (part, rotation) in idle.layer().iter().chain(control.iter()) {
+        let transform = body.relative.part_mut(*part);
+        transform.rotation = transform.rotation * *rotation;
+    }
+    // Sideways for the body, whichever way it faces.
+    let shift = body.relative.root().rotation * idle.shift();
+    body.relative.root_mut().translation += shift - *applied_shift;
+    *applied_shift = shift;
+    body.absolute = body.relative.propagated();
 }
 
-pub fn angular_spring_system<T: Player, Parent: Component, Child: Component>(
+pub fn animate_idle<T: Player>(time: Res<Time>, mut idle: ResMut<IdleAnimation<T>>) {
+    if idle.enabled {
+        idle.tick(time.delta_seconds());
+    }
+}
+
+pub fn tick_pose_blend<T: Player>(time: Res<Time>, mut blend: ResMut<PoseBlend<T>>) {
+    if blend.is_blending() {
+        blend.tick(time.delta_seconds());
+    }
+}
+
+/// Points the driven motors at the pose. The other motors are tuned when they are created or
+/// the tuning changes.
+pub fn angular_spring_system<T: Player>(
     body: Res<PlayerBody<T>>,
-    joint: Query<(&JointHandleComponent, &JointMotorParams), (With<Joint<Parent, Child>>, With<T>)>,
+    tuning: Res<MotorTuning>,
+    joint: Query<(Entity, &Joint, &JointHandleComponent, &JointMotorParams), With<T>>,
+    added: Query<Entity, (With<T>, Added<JointHandleComponent>)>,
     mut joints: ResMut<ImpulseJointSet>,
 ) {
-    if let Ok((joint_handle, JointMotorParams { stiffness, damping })) = joint.single() {
+    for (entity, joint, joint_handle, params) in joint.iter() {
+        let JointMotorParams { stiffness, damping } = tuning.params(joint.child, params);
+        if !joint.driven {
+            if tuning.is_changed() || added.get(entity).is_ok() {
+                if let Some(joint) = joints.get_mut(joint_handle.handle()) {
+                    joint.data = joint
+                        .data
+                        .motor_position(JointAxis::AngX, 0.0, stiffness, damping)
+                        .motor_position(JointAxis::AngY, 0.0, stiffness, damping)
+                        .motor_position(JointAxis::AngZ, 0.0, stiffness, damping);
+                }
+            }
+            continue;
+        }
+        let transform = body.relative.part(joint.parent);
         let joint = joints.get_mut(joint_handle.handle()).unwrap();
-        let transform = body.relative.get::<Parent>();
         let (x, y, z, w) = (
             transform.rotation.x,
             transform.rotation.y,
@@ -112,8 +364,8 @@ pub fn angular_spring_system<T: Player, Parent: Component, Child: Component>(
 
         joint.data = joint
             .data
-            .motor_position(JointAxis::AngX, pitch, *stiffness, *damping)
-            .motor_position(JointAxis::AngY, yaw, *stiffness, *damping)
-            .motor_position(JointAxis::AngZ, roll, *stiffness, *damping);
+            .motor_position(JointAxis::AngX, pitch, stiffness, damping)
+            .motor_position(JointAxis::AngY, yaw, stiffness, damping)
+            .motor_position(JointAxis::AngZ, roll, stiffness, damping);
     }
 }
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy_rapier3d::{physics::JointHandleComponent, prelude::*};
+
+use crate::components::{
+    body::part::BodyPartId,
+    control::HandControl,
+    grab::{is_grabbable, Grab, GrabJoint, Grabs, Hand, BREAK_FORCE, GRAB_GRIP},
+    player::{Player, Player1, Player2},
+};
+
+/// Holds on to the partner where a gripping hand of the local player touches it.
+pub fn detect_grabs(
+    control: Res<HandControl<Player1>>,
+    mut grabs: ResMut<Grabs<Player1>>,
+    narrow_phase: Res<NarrowPhase>,
+    hands: Query<(Entity, &BodyPartId), With<Player1>>,
+    partner: Query<&BodyPartId, With<Player2>>,
+) {
+    for hand in Hand::BOTH {
+        let grip = match hand {
+            Hand::Left => control.grip().x,
+            Hand::Right => control.grip().y,
+        };
+        let current = grabs.get(hand);
+        let next = match current {
+            _ if grip < GRAB_GRIP => Grab::Free,
+            Grab::Free => Grab::from_target(touched(hand, &narrow_phase, &hands, &partner)),
+            _ => current,
+        };
+        if next != current {
+            *grabs.get_mut(hand) = next;
+        }
+    }
+}
+
+fn touched(
+    hand: Hand,
+    narrow_phase: &NarrowPhase,
+    hands: &Query<(Entity, &BodyPartId), With<Player1>>,
+    partner: &Query<&BodyPartId, With<Player2>>,
+) -> Option<BodyPartId> {
+    hands
+        .iter()
+        .filter(|(_, part)| hand.parts().contains(*part))
+        .find_map(|(entity, _)| {
+            let collider: ColliderHandle = entity.handle();
+            narrow_phase
+                .contacts_with(collider)
+                .filter(|pair| pair.has_any_active_contact)
+                .find_map(|pair| {
+                    let other = if pair.collider1 == collider {
+                        pair.collider2
+                    } else {
+                        pair.collider1
+                    };
+                    partner
+                        .get(other.entity())
+                        .ok()
+                        .copied()
+                        .filter(|part| is_grabbable(*part))
+                })
+        })
+}
+
+/// Creates and removes the joints of `T` holding `U`, following `Grabs<T>`.
+pub fn apply_grabs<T: Player, U: Player>(
+    mut commands: Commands,
+    grabs: Res<Grabs<T>>,
+    joints: Query<(Entity, &GrabJoint), With<T>>,
+    hands: Query<(Entity, &BodyPartId, &RigidBodyPositionComponent), With<T>>,
+    partner: Query<(Entity, &BodyPartId, &RigidBodyPositionComponent), With<U>>,
+) {
+    if !grabs.is_changed() {
+        return;
+    }
+    for hand in Hand::BOTH {
+        let target = grabs.get(hand).target();
+        if let Some((entity, joint)) = joints.iter().find(|(_, joint)| joint.hand == hand) {
+            if Some(joint.target) == target {
+                continue;
+            }
+            commands.entity(entity).despawn();
+        }
+        let target = match target {
+            Some(target) => target,
+            None => continue,
+        };
+        let holder = hand
+            .parts()
+            .iter()
+            .find_map(|part| hands.iter().find(|(_, id, _)| *id == part));
+        let held = partner.iter().find(|(_, id, _)| **id == target);
+        if let (Some((holder, _, from)), Some((held, _, to))) = (holder, held) {
+            // Hold the partner where the hand is now.
+            let anchor = to.0.position.inverse() * Point::from(from.0.position.translation.vector);
+            let joint = SphericalJoint::new().local_anchor2(anchor);
+            commands
+                .spawn()
+                .insert(T::default())
+                .insert(GrabJoint { hand, target })
+                .insert(JointBuilderComponent::new(joint, holder, held));
+        }
+    }
+}
+
+/// Lets go of the partner when pulled too hard, or when either body is gone.
+pub fn break_grabs<T: Player>(
+    mut grabs: ResMut<Grabs<T>>,
+    joints: Query<(&GrabJoint, &JointHandleComponent), With<T>>,
+    joint_set: Res<ImpulseJointSet>,
+    integration: Res<IntegrationParameters>,
+) {
+    for (joint, handle) in joints.iter() {
+        let force = joint_set
+            .get(handle.handle())
+            .map(|held| held.impulses.norm() / integration.dt);
+        let torn = force.map_or(true, |force| force > BREAK_FORCE);
+        if torn && grabs.get(joint.hand) == Grab::Holding(joint.target) {
+            *grabs.get_mut(joint.hand) = Grab::Broken;
+        }
+    }
+}
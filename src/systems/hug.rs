@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::components::{
+    body::part::{BodyPartId, Chest},
+    hug::{
+        HugEnded, HugSample, HugScore, HugStarted, HugStats, PlayerContacts, HUG_END_DELAY,
+        HUG_START_DELAY,
+    },
+    physics::{BodyContact, ContactPhase},
+    player::{Player1, Player2, PlayerId},
+};
+
+/// Keeps track of which parts of the two players touch each other.
+pub fn track_contacts(mut events: EventReader<BodyContact>, mut contacts: ResMut<PlayerContacts>) {
+    for event in events.iter() {
+        let pair = match event.part_of(PlayerId::Player1) {
+            Some(pair) if event.player_a != event.player_b => pair,
+            _ => continue,
+        };
+        match event.phase {
+            ContactPhase::Started => contacts.0.insert(pair),
+            ContactPhase::Stopped => contacts.0.remove(&pair),
+        };
+    }
+}
+
+/// Starts a hug when both players wrap their arms around each other, and ends it when either
+/// lets go. Brief changes of contact are ignored.
+pub fn detect_hug(
+    time: Res<Time>,
+    contacts: Res<PlayerContacts>,
+    mut stats: ResMut<HugStats>,
+    mut started: EventWriter<HugStarted>,
+    mut ended: EventWriter<HugEnded>,
+) {
+    let wrapping = contacts.player1_wraps() && contacts.player2_wraps();
+    if stats.hugging {
+        stats.current += time.delta();
+        stats.total += time.delta();
+    }
+    if wrapping == stats.hugging {
+        stats.pending = Default::default();
+        return;
+    }
+    stats.pending += time.delta();
+    let delay = if stats.hugging {
+        HUG_END_DELAY
+    } else {
+        HUG_START_DELAY
+    };
+    if stats.pending < delay {
+        return;
+    }
+    if stats.hugging {
+        // The hug was over when the contacts were lost.
+        let pending = stats.pending;
+        stats.current -= pending;
+        stats.total -= pending;
+        stats.longest = stats.longest.max(stats.current);
+        ended.send(HugEnded {
+            duration: stats.current,
+        });
+    } else {
+        // The hug began when the contacts were made.
+        stats.count += 1;
+        stats.current = stats.pending;
+        let pending = stats.pending;
+        stats.total += pending;
+        started.send(HugStarted);
+    }
+    stats.hugging = !stats.hugging;
+    stats.pending = Default::default();
+}
+
+/// Samples the physics scene while hugging.
+pub fn score_hug(
+    time: Res<Time>,
+    stats: Res<HugStats>,
+    contacts: Res<PlayerContacts>,
+    narrow_phase: Res<NarrowPhase>,
+    mut score: ResMut<HugScore>,
+    mut started: EventReader<HugStarted>,
+    mut ended: EventReader<HugEnded>,
+    players1: Query<(Entity, &BodyPartId), With<Player1>>,
+    players2: Query<&BodyPartId, With<Player2>>,
+    chests: Query<
+        (&RigidBodyPositionComponent, &RigidBodyVelocityComponent),
+        (With<Chest>, Or<(With<Player1>, With<Player2>)>),
+    >,
+) {
+    if started.iter().count() > 0 {
+        score.restart();
+    }
+    if ended.iter().count() > 0 {
+        score.best = score.best.max(score.score);
+    }
+    if !stats.hugging {
+        return;
+    }
+    let points = players1
+        .iter()
+        .map(|(entity, _)| {
+            let collider: ColliderHandle = entity.handle();
+            narrow_phase
+                .contacts_with(collider)
+                .filter(|pair| {
+                    let other = if pair.collider1 == collider {
+                        pair.collider2
+                    } else {
+                        pair.collider1
+                    };
+                    players2.get(other.entity()).is_ok()
+                })
+                .flat_map(|pair| pair.manifolds.iter())
+                .map(|manifold| manifold.data.solver_contacts.len())
+                .sum::<usize>()
+        })
+        .sum();
+    let chests: Vec<_> = chests.iter().collect();
+    let (distance, speed) = match chests.as_slice() {
+        [(position1, velocity1), (position2, velocity2)] => (
+            (position1.0.position.translation.vector - position2.0.position.translation.vector)
+                .norm(),
+            (velocity1.0.linvel - velocity2.0.linvel).norm(),
+        ),
+        _ => return,
+    };
+    let sample = HugSample::new(points, &contacts, distance, speed);
+    score.add(sample, time.delta_seconds());
+}
+
+pub fn log_hugs(
+    score: Res<HugScore>,
+    mut started: EventReader<HugStarted>,
+    mut ended: EventReader<HugEnded>,
+) {
+    for _ in started.iter() {
+        info!("hug started");
+    }
+    for event in ended.iter() {
+        info!(
+            "hug ended after {:.1}s with a score of {:.0}",
+            event.duration.as_secs_f32(),
+            score.score
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier3d::{
+    na::{Quaternion, UnitQuaternion},
+    physics::JointHandleComponent,
+    prelude::*,
+};
+
+use crate::components::{
+    body::PlayerBody,
+    locomotion::{ground, FootPlant, Stance, STEP_DISTANCE, STEP_HEIGHT, STEP_SECONDS},
+    physics::GroundAnchor,
+    player::{Player, Player1},
+};
+
+/// Arrow keys walk and `Z`/`X` turn.
+pub fn walk_input(
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    mut stance: ResMut<Stance<Player1>>,
+) {
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        input.pressed(positive) as u8 as f32 - input.pressed(negative) as u8 as f32
+    };
+    let forward = axis(KeyCode::Up, KeyCode::Down);
+    let sideways = axis(KeyCode::Right, KeyCode::Left);
+    let turn = axis(KeyCode::Z, KeyCode::X);
+    if forward != 0.0 || sideways != 0.0 || turn != 0.0 {
+        stance.walk(forward, sideways, turn, time.delta_seconds());
+    }
+}
+
+/// Moves the ground of a player to where they stand, and the root of their body with it.
+pub fn move_stance<T: Player>(
+    stance: Res<Stance<T>>,
+    mut body: ResMut<PlayerBody<T>>,
+    mut moved: Local<Option<Transform>>,
+    mut grounds: Query<&mut RigidBodyPositionComponent, (With<T>, With<GroundAnchor>)>,
+) {
+    if !stance.is_changed() {
+        return;
+    }
+    let transform = stance.transform();
+    // The body is spawned where the player stands.
+    let previous = moved.replace(transform).unwrap_or(transform);
+    let delta = transform.compute_matrix() * previous.compute_matrix().inverse();
+    let root = body.relative.root_mut();
+    *root = Transform::from_matrix(delta * root.compute_matrix());
+    body.absolute = body.relative.propagated();
+    for mut position in grounds.iter_mut() {
+        position.0.next_position = to_isometry(&ground(&transform));
+    }
+}
+
+/// Steps the feet one at a time, once they are too far from where they would stand.
+pub fn plant_feet<T: Player>(
+    time: Res<Time>,
+    stance: Res<Stance<T>>,
+    mut feet: Query<(&mut FootPlant, &JointHandleComponent), With<T>>,
+    mut joints: ResMut<ImpulseJointSet>,
+) {
+    let ground = ground(&stance.transform());
+    let to_ground = ground.compute_matrix().inverse();
+    let mut stepping = feet.iter_mut().any(|(foot, _)| foot.step.is_some());
+    for (mut foot, handle) in feet.iter_mut() {
+        let target = ground.mul_vec3(foot.rest);
+        let planted = match foot.step {
+            Some((from, progress)) => {
+                let progress = (progress + time.delta_seconds() / STEP_SECONDS).min(1.0);
+                foot.step = Some((from, progress)).filter(|_| progress < 1.0);
+                from.lerp(target, progress) + Vec3::Y * STEP_HEIGHT * (progress * PI).sin()
+            }
+            None if !stepping && foot.planted.distance(target) > STEP_DISTANCE => {
+                stepping = true;
+                foot.step = Some((foot.planted, 0.0));
+                continue;
+            }
+            None if stance.is_changed() => foot.planted,
+            None => continue,
+        };
+        foot.planted = planted;
+        // The anchor is relative to the ground, which moves with the stance.
+        let anchor = to_ground.transform_point3(planted);
+        if let Some(joint) = joints.get_mut(handle.handle()) {
+            joint.data = joint
+                .data
+                .local_anchor1(point!(anchor.x, anchor.y, anchor.z));
+        }
+    }
+}
+
+pub fn to_isometry(transform: &Transform) -> Isometry<Real> {
+    let (translation, rotation) = (transform.translation, transform.rotation);
+    Isometry::from_parts(
+        vector!(translation.x, translation.y, translation.z).into(),
+        UnitQuaternion::new_normalize(Quaternion::new(
+            rotation.w, rotation.x, rotation.y, rotation.z,
+        )),
+    )
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::components::{
+    body::part::{Chest, Head},
+    control::HandControl,
+    hug::HugStats,
+    look::{HeadLook, LOOK_SPEED},
+    player::Player,
+};
+
+/// Turns the head of `T` toward the head of `U`, unless overridden by the hand control.
+/// The head rests on the partner's shoulder while hugging.
+pub fn look_at_partner<T: Player, U: Player>(
+    time: Res<Time>,
+    control: Res<HandControl<T>>,
+    stats: Option<Res<HugStats>>,
+    mut look: ResMut<HeadLook<T>>,
+    chest: Query<&RigidBodyPositionComponent, (With<T>, With<Chest>)>,
+    head: Query<&RigidBodyPositionComponent, (With<T>, With<Head>)>,
+    partner: Query<&RigidBodyPositionComponent, (With<U>, With<Head>)>,
+) {
+    if !look.enabled {
+        return;
+    }
+    let target = if let Some(input) = control.look() {
+        HeadLook::<T>::rotation_of_input(input)
+    } else if stats.map_or(false, |stats| stats.hugging) {
+        HeadLook::<T>::resting()
+    } else {
+        match (chest.single(), head.single(), partner.single()) {
+            (Ok(chest), Ok(head), Ok(partner)) => {
+                let direction = chest.0.position.rotation.inverse_transform_vector(
+                    &(partner.0.position.translation.vector - head.0.position.translation.vector),
+                );
+                HeadLook::<T>::rotation_toward(Vec3::new(direction.x, direction.y, direction.z))
+            }
+            _ => return,
+        }
+    };
+    if target != look.rotation() {
+        look.turn_toward(target, LOOK_SPEED * time.delta_seconds());
+    }
+}
@@ -1,11 +1,24 @@
 pub mod active_ragdoll;
+pub mod assist;
+pub mod avatar;
+pub mod contact;
 pub mod control;
+pub mod grab;
+pub mod hug;
 pub mod joint_display;
-#[cfg(target_arch = "wasm32")]
+pub mod locomotion;
+pub mod look;
 pub mod networking;
 pub mod name;
+pub mod pose;
+#[cfg(target_arch = "wasm32")]
+pub mod replay;
+pub mod rig;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod room;
 pub mod scene;
 pub mod setup_player;
+pub mod tuning;
 #[cfg(target_arch = "wasm32")]
 pub mod ui;
 #[cfg(target_arch = "wasm32")]
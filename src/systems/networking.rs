@@ -1,18 +1,27 @@
 pub mod event_handlers;
 pub mod handle_event;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lan;
 pub mod sync;
+#[cfg(target_arch = "wasm32")]
 pub mod transport;
+pub mod validation;
 
 use bevy::{ecs::schedule::ShouldRun, prelude::*};
 use parking_lot::Mutex;
 
 use crate::components::{
-    networking::{ElapsedTime, HugCommand, PlayerName, Sender, WaitTimer},
+    name_policy::NamePolicy,
+    networking::{HugCommand, PeerViolations, PlayerName, Sender, WaitTimer},
     player::Player1,
     state::AppState,
-    ui::{Message, Messages},
+    ui::{Alert, Message, Messages},
 };
 
+/// Strikes that are forgiven per second.
+const STRIKE_DECAY: f32 = 0.5;
+const MAX_STRIKES: f32 = 10.0;
+
 lazy_static::lazy_static! {
     pub static ref BUFFER: Mutex<Vec<String>> = Mutex::new(Vec::new());
     pub static ref SEND_BUFFER: Mutex<Vec<String>> = Mutex::new(Vec::new());
@@ -30,6 +39,7 @@ pub fn when_connect(state: Res<State<AppState>>) -> ShouldRun {
     }
 }
 
+#[cfg(target_arch = "wasm32")]
 pub fn join_room(
     mut sender: ResMut<Sender>,
     mut state: ResMut<State<AppState>>,
@@ -47,15 +57,31 @@ pub fn join_room(
     }
 }
 
-pub fn update_name(mut name: ResMut<PlayerName<Player1>>) {
+pub fn update_name(
+    mut name: ResMut<PlayerName<Player1>>,
+    mut raw: Local<String>,
+    policy: Res<NamePolicy>,
+    state: Res<State<AppState>>,
+) {
+    // The replayed names are shown instead of ours.
+    if *state.current() == AppState::Replaying {
+        raw.clear();
+        return;
+    }
     let name_value = NAME.lock();
-    if *name_value != name.0 {
-        name.0 = name_value.clone();
+    if *name_value != *raw {
+        *raw = name_value.clone();
+        name.0 = policy.sanitize(&raw);
     }
 }
 
-pub fn elapse_time(mut elapsed_time: ResMut<ElapsedTime>, time: Res<Time>) {
-    elapsed_time.0 += time.delta();
+#[cfg(target_arch = "wasm32")]
+pub fn load_name_policy(mut policy: ResMut<NamePolicy>) {
+    let window = web_sys::window().unwrap();
+    let storage = window.local_storage().unwrap().unwrap();
+    if let Ok(Some(list)) = storage.get_item("word_filter") {
+        *policy = NamePolicy::default().with_blocked_words(&list);
+    }
 }
 
 pub fn back_to_alone(
@@ -64,8 +90,34 @@ pub fn back_to_alone(
     mut state: ResMut<State<AppState>>,
     mut message: ResMut<Message>,
 ) {
-    if *state.current() != AppState::Connected && wait_timer.0.tick(time.delta()).just_finished() {
+    let current = state.current();
+    if *current != AppState::Connected
+        && *current != AppState::Replaying
+        && wait_timer.0.tick(time.delta()).just_finished()
+    {
         let _ = state.set(AppState::Alone);
         message.0 = "".into();
     }
 }
+
+pub fn disconnect_offender(
+    mut violations: ResMut<PeerViolations>,
+    time: Res<Time>,
+    mut sender: ResMut<Sender>,
+    mut state: ResMut<State<AppState>>,
+    mut alert: ResMut<Alert>,
+    messages: Res<Messages>,
+) {
+    if violations.strikes > MAX_STRIKES {
+        warn!(
+            "disconnecting the partner after {} invalid payloads",
+            violations.total
+        );
+        violations.strikes = 0.0;
+        sender.0.push(HugCommand::Leave);
+        alert.0 = messages.disconnected.into();
+        let _ = state.set(AppState::Alone);
+    } else {
+        violations.strikes = (violations.strikes - STRIKE_DECAY * time.delta_seconds()).max(0.0);
+    }
+}
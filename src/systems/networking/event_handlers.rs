@@ -1,15 +1,16 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::{RigidBodyType, RigidBodyTypeComponent};
 
-use crate::{
-    components::{
-        body::part::Head,
-        control::HandControl,
-        networking::{ElapsedTime, HugCommand, Payload, PlayerName, Sender, WaitTimer},
-        player::{Player1, Player2},
-        ui::{Message, Messages},
-    },
-    systems::setup_player::PLAYER2_COLOR,
+use crate::components::{
+    avatar::PlayerAvatar,
+    body::part::Head,
+    control::HandControl,
+    grab::Grabs,
+    hug::{HugScore, HugStats},
+    locomotion::Stance,
+    networking::{HugCommand, Payload, PeerViolations, PlayerName, Sender, WaitTimer},
+    player::{Player1, Player2},
+    ui::{Message, Messages},
 };
 
 pub fn random_matching(
@@ -31,31 +32,57 @@ pub fn create_room(mut sender: ResMut<Sender>, mut wait_timer: ResMut<WaitTimer>
 pub fn cleanup(
     mut name: ResMut<PlayerName<Player2>>,
     mut control: ResMut<HandControl<Player2>>,
-    mut time: ResMut<ElapsedTime>,
+    mut avatar: ResMut<PlayerAvatar<Player2>>,
+    mut grabs: (ResMut<Grabs<Player1>>, ResMut<Grabs<Player2>>),
+    mut hug: (ResMut<HugStats>, ResMut<HugScore>),
+    mut stance: ResMut<Stance<Player2>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     head: Query<&Handle<StandardMaterial>, (With<Player2>, With<Head>)>,
 ) {
     name.0 = "".into();
     *control = HandControl::default_absent();
-    time.0 = Default::default();
-    let material = materials.get_mut(head.single().unwrap()).unwrap();
-    material.unlit = true;
-    material.base_color = Color::rgb(0.5, 0.5, 0.9);
+    *avatar = PlayerAvatar::default();
+    *grabs.0 = Grabs::default();
+    *grabs.1 = Grabs::default();
+    *hug.0 = HugStats::default();
+    *hug.1 = HugScore::default();
+    stance.set(Vec2::ZERO, 0.0);
+    // Players rendered by a rig have no material.
+    if let Some(material) = head.single().ok().and_then(|head| materials.get_mut(head)) {
+        material.unlit = true;
+        material.base_color = Color::rgb(0.5, 0.5, 0.9);
+    }
 }
 
 pub fn on_connected(
     mut commands: Commands,
     mut sender: ResMut<Sender>,
     name: Res<PlayerName<Player1>>,
+    avatars: (Res<PlayerAvatar<Player1>>, Res<PlayerAvatar<Player2>>),
+    stance: Res<Stance<Player1>>,
     mut control: ResMut<HandControl<Player1>>,
+    mut violations: ResMut<PeerViolations>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     head: Query<&Handle<StandardMaterial>, (With<Player2>, With<Head>)>,
 ) {
     sender.0.push(HugCommand::Push {
         payload: Payload::Name(name.0.clone()),
     });
+    let (avatar1, avatar2) = avatars;
+    sender.0.push(HugCommand::Push {
+        payload: Payload::Avatar(avatar1.0.clone()),
+    });
+    sender.0.push(HugCommand::Push {
+        payload: Payload::Stance {
+            offset: stance.offset(),
+            facing: stance.facing(),
+        },
+    });
     *control = HandControl::default();
-    let material = materials.get_mut(head.single().unwrap()).unwrap();
-    material.unlit = false;
-    material.base_color = PLAYER2_COLOR;
+    *violations = PeerViolations::default();
+    // Players rendered by a rig have no material.
+    if let Some(material) = head.single().ok().and_then(|head| materials.get_mut(head)) {
+        material.unlit = false;
+        material.base_color = avatar2.0.color();
+    }
 }
@@ -1,16 +1,31 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+#[cfg(target_arch = "wasm32")]
+use crate::adapters::share::navigator_share;
 use crate::{
-    adapters::share::navigator_share,
     components::{
+        avatar::PlayerAvatar,
         body::part::*,
         control::HandControl,
-        networking::{HugEvent, IsPrimary, Payload, PlayerName, Receiver},
-        player::{Player1, Player2},
+        grab::{Grab, Grabs},
+        locomotion::Stance,
+        name_policy::NamePolicy,
+        networking::{
+            Authority, HugEvent, IsPrimary, PartState, Payload, PeerViolations, PlayerName,
+            Receiver,
+        },
+        player::{partner_to_players, Placement, Player, Player1, Player2, PlayerId},
+        pose::PoseBlend,
         state::AppState,
         ui::{Alert, Message, Messages},
     },
+    systems::locomotion::to_isometry,
+};
+
+use super::validation::{
+    limit_correction, validate_avatar, validate_displacement, validate_grab, validate_hand_control,
+    validate_name, validate_pose, validate_position, validate_stance,
 };
 
 pub fn handle_events(
@@ -20,8 +35,17 @@ pub fn handle_events(
     mut message: ResMut<Message>,
     mut alert: ResMut<Alert>,
     mut position: Query<&mut RigidBodyPositionComponent>,
-    mut is_primary_res: ResMut<IsPrimary>,
-    mut name2: ResMut<PlayerName<Player2>>,
+    roles: (ResMut<IsPrimary>, ResMut<Authority>),
+    peer: (
+        ResMut<PlayerName<Player2>>,
+        ResMut<PeerViolations>,
+        Res<NamePolicy>,
+        ResMut<PoseBlend<Player2>>,
+        ResMut<PlayerAvatar<Player2>>,
+        ResMut<Grabs<Player2>>,
+        ResMut<Stance<Player2>>,
+        (Res<Placement<Player1>>, Res<Placement<Player2>>),
+    ),
     player1_head_q: Query<Entity, (With<Player1>, With<Head>)>,
     player2_head_q: Query<Entity, (With<Player2>, With<Head>)>,
     player1_hip_q: Query<Entity, (With<Player1>, With<Hip>)>,
@@ -32,9 +56,24 @@ pub fn handle_events(
     player2_hand_right_q: Query<Entity, (With<Player2>, With<HandRight>)>,
 ) {
     let (receiver, messages) = receiver;
+    let (mut is_primary_res, mut authority) = roles;
+    let (
+        mut name2,
+        mut violations,
+        policy,
+        mut pose2,
+        mut avatar2,
+        mut grabs2,
+        mut stance2,
+        placements,
+    ) = peer;
+    let (partner_to_player1, partner_to_player2) = partner_to_players(&placements.0, &placements.1);
     for event in receiver.0.iter() {
         match event {
-            HugEvent::Joined { is_primary } => {
+            HugEvent::Joined {
+                is_primary,
+                authoritative,
+            } => {
                 message.0 = messages.ready.into();
                 let _ = state.set(AppState::Connected);
                 *is_primary_res = if *is_primary {
@@ -42,19 +81,61 @@ pub fn handle_events(
                 } else {
                     IsPrimary::No
                 };
+                *authority = if *authoritative {
+                    Authority::Server
+                } else {
+                    Authority::Peer
+                };
             }
+            #[cfg(target_arch = "wasm32")]
             HugEvent::RoomCreated { key } => unsafe {
                 let url = &format!("?key={}", key);
                 navigator_share(messages.room_link, url, &mut alert, &messages);
                 message.0 = messages.room_created.into();
             },
+            #[cfg(not(target_arch = "wasm32"))]
+            HugEvent::RoomCreated { key } => {
+                message.0 = messages.hosting.replace("{address}", key);
+            }
             HugEvent::NotFound => {
                 alert.0 = messages.room_notfound.into();
             }
+            HugEvent::Left => {
+                alert.0 = messages.left.into();
+                let _ = state.set(AppState::Alone);
+            }
             HugEvent::Push { payload } => match payload {
-                Payload::HandControl { left, right } => {
+                Payload::HandControl {
+                    left,
+                    right,
+                    grip,
+                    torso,
+                    look,
+                    reach,
+                    assist,
+                } => {
+                    if let Err(violation) =
+                        validate_hand_control(left, right, grip, torso, look, reach, *assist)
+                    {
+                        violations.record(violation);
+                        if !left.is_finite()
+                            || !right.is_finite()
+                            || !grip.is_finite()
+                            || !torso.is_finite()
+                            || !look.map_or(true, |look| look.is_finite())
+                            || !reach.map_or(true, |reach| reach.is_finite())
+                            || !assist.is_finite()
+                        {
+                            continue;
+                        }
+                    }
                     control.set_left(left.clone());
                     control.set_right(right.clone());
+                    control.set_grip(grip.clone());
+                    control.set_torso(torso.clone());
+                    control.set_look(*look);
+                    control.set_reach(*reach);
+                    control.set_assist(*assist);
                 }
                 Payload::Sync {
                     // Swap player1 and player2
@@ -67,57 +148,168 @@ pub fn handle_events(
                     player1_hand_right: player2_hand_right,
                     player2_hand_right: player1_hand_right,
                 } => {
-                    position_vec(
+                    sync_position(
                         &mut position.get_mut(player1_head_q.single().unwrap()).unwrap(),
-                        player1_head,
+                        &partner_to_player1.transform_point3(*player1_head),
+                        &*placements.0,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position.get_mut(player2_head_q.single().unwrap()).unwrap(),
-                        player2_head,
+                        &partner_to_player2.transform_point3(*player2_head),
+                        &*placements.1,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position.get_mut(player1_hip_q.single().unwrap()).unwrap(),
-                        player1_hip,
+                        &partner_to_player1.transform_point3(*player1_hip),
+                        &*placements.0,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position.get_mut(player2_hip_q.single().unwrap()).unwrap(),
-                        player2_hip,
+                        &partner_to_player2.transform_point3(*player2_hip),
+                        &*placements.1,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position
                             .get_mut(player1_hand_left_q.single().unwrap())
                             .unwrap(),
-                        player1_hand_left,
+                        &partner_to_player1.transform_point3(*player1_hand_left),
+                        &*placements.0,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position
                             .get_mut(player2_hand_left_q.single().unwrap())
                             .unwrap(),
-                        player2_hand_left,
+                        &partner_to_player2.transform_point3(*player2_hand_left),
+                        &*placements.1,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position
                             .get_mut(player1_hand_right_q.single().unwrap())
                             .unwrap(),
-                        player1_hand_right,
+                        &partner_to_player1.transform_point3(*player1_hand_right),
+                        &*placements.0,
+                        &mut violations,
                     );
-                    position_vec(
+                    sync_position(
                         &mut position
                             .get_mut(player2_hand_right_q.single().unwrap())
                             .unwrap(),
-                        player2_hand_right,
+                        &partner_to_player2.transform_point3(*player2_hand_right),
+                        &*placements.1,
+                        &mut violations,
                     );
                 }
+                // Applied by `apply_state`, and only from a room server.
+                Payload::State { .. } => {}
                 Payload::Name(name) => {
-                    name2.0 = name.clone();
+                    if let Err(violation) = validate_name(name) {
+                        violations.record(violation);
+                    }
+                    name2.0 = policy.sanitize(name);
+                }
+                Payload::Pose { pose, seconds } => {
+                    if let Err(violation) = validate_pose(pose, *seconds) {
+                        violations.record(violation);
+                        continue;
+                    }
+                    pose2.blend_to(pose.clone(), *seconds);
+                }
+                Payload::Avatar(avatar) => {
+                    if let Err(violation) = validate_avatar(avatar) {
+                        violations.record(violation);
+                        continue;
+                    }
+                    avatar2.0 = avatar.clone();
+                }
+                Payload::Grab { left, right } => {
+                    if let Err(violation) = validate_grab(*left).and(validate_grab(*right)) {
+                        violations.record(violation);
+                        continue;
+                    }
+                    grabs2.left = Grab::from_target(*left);
+                    grabs2.right = Grab::from_target(*right);
+                }
+                Payload::Stance { offset, facing } => {
+                    if let Err(violation) = validate_stance(offset, *facing) {
+                        violations.record(violation);
+                        if !offset.is_finite() || !facing.is_finite() {
+                            continue;
+                        }
+                    }
+                    stance2.set(*offset, *facing);
                 }
             },
         }
     }
 }
 
-fn position_vec(position: &mut RigidBodyPositionComponent, vec: &Vec3) {
-    position.0.next_position.translation.x = vec.x;
-    position.0.next_position.translation.y = vec.y;
-    position.0.next_position.translation.z = vec.z;
+fn sync_position<T>(
+    position: &mut RigidBodyPositionComponent,
+    vec: &Vec3,
+    placement: &Placement<T>,
+    violations: &mut PeerViolations,
+) {
+    if let Err(violation) = validate_position(vec, placement) {
+        violations.record(violation);
+        return;
+    }
+    let current = position.0.position.translation;
+    let current = Vec3::new(current.x, current.y, current.z);
+    // Still applied, but only partway.
+    if let Err(violation) = validate_displacement(&current, vec) {
+        violations.record(violation);
+    }
+    let next = limit_correction(current, *vec);
+    position.0.next_position.translation.x = next.x;
+    position.0.next_position.translation.y = next.y;
+    position.0.next_position.translation.z = next.z;
+}
+
+/// Moves every part of `T` to where the room server simulates it.
+pub fn apply_state<T: Player>(
+    receiver: Res<Receiver>,
+    authority: Res<Authority>,
+    placements: (Res<Placement<Player1>>, Res<Placement<Player2>>),
+    mut parts: Query<(&BodyPartId, &mut RigidBodyPositionComponent), With<T>>,
+) {
+    if *authority != Authority::Server {
+        return;
+    }
+    let (partner_to_player1, partner_to_player2) = partner_to_players(&placements.0, &placements.1);
+    for event in receiver.0.iter() {
+        if let HugEvent::Push {
+            payload: Payload::State { player1, player2 },
+        } = event
+        {
+            // Swap player1 and player2 like a sync.
+            let (states, partner_to_player) = match T::id() {
+                PlayerId::Player1 => (player2, partner_to_player1),
+                PlayerId::Player2 => (player1, partner_to_player2),
+            };
+            let partner_to_player = Transform::from_matrix(partner_to_player);
+            for (part, mut position) in parts.iter_mut() {
+                if let Some(state) = states.iter().find(|state| state.part == *part) {
+                    apply_part_state(&mut position, state, &partner_to_player);
+                }
+            }
+        }
+    }
+}
+
+fn apply_part_state(
+    position: &mut RigidBodyPositionComponent,
+    state: &PartState,
+    partner_to_player: &Transform,
+) {
+    // The server is trusted, but a diverged simulation must not poison ours.
+    if !state.translation.is_finite() || !state.rotation.is_finite() {
+        return;
+    }
+    position.0.next_position = to_isometry(&state.mapped(partner_to_player).transform());
 }
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+
+use crate::{
+    adapters::{discovery::LanDiscovery, lan::LanTransport},
+    components::{
+        name_policy::NamePolicy,
+        networking::{HugCommand, LanConfig, PlayerName, Receiver, Sender},
+        player::Player1,
+        state::AppState,
+        ui::{Alert, Message, Messages},
+    },
+};
+
+const JOIN_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+pub struct DiscoveryText;
+
+use super::NAME;
+
+pub fn start_lan(
+    config: Res<LanConfig>,
+    mut sender: ResMut<Sender>,
+    mut state: ResMut<State<AppState>>,
+    mut message: ResMut<Message>,
+    messages: Res<Messages>,
+) {
+    if let Some(name) = &config.name {
+        *NAME.lock() = name.clone();
+    }
+    if config.host {
+        let _ = state.set(AppState::CreatingRoom);
+    } else if let Some(key) = &config.join {
+        message.0 = messages.finding_room.into();
+        sender.0.push(HugCommand::JoinRoom { key: key.clone() });
+        let _ = state.set(AppState::MatchingByKey);
+    }
+}
+
+pub fn sender(
+    mut sender: ResMut<Sender>,
+    mut transport: ResMut<LanTransport>,
+    mut discovery: ResMut<LanDiscovery>,
+) {
+    for command in sender.0.split_off(0) {
+        match command {
+            // Without a server, match with a LAN host instead.
+            HugCommand::JoinRandom if !transport.has_server() => discovery.matching = true,
+            command => transport.handle_command(command),
+        }
+    }
+}
+
+pub fn receiver(mut receiver: ResMut<Receiver>, mut transport: ResMut<LanTransport>) {
+    receiver.0 = transport.poll();
+}
+
+/// Native builds have no HTML overlay, so messages go to the log.
+pub fn log_messages(message: Res<Message>, alert: Res<Alert>) {
+    if message.is_changed() && !message.0.is_empty() {
+        info!("{}", message.0);
+    }
+    if alert.is_changed() && !alert.0.is_empty() {
+        warn!("{}", alert.0);
+    }
+}
+
+pub fn discover(
+    mut discovery: ResMut<LanDiscovery>,
+    transport: Res<LanTransport>,
+    name: Res<PlayerName<Player1>>,
+) {
+    discovery.poll();
+    if transport.is_hosting() {
+        discovery.announce(&name.0, transport.port);
+    }
+}
+
+pub fn match_random(
+    mut discovery: ResMut<LanDiscovery>,
+    mut sender: ResMut<Sender>,
+    state: Res<State<AppState>>,
+) {
+    if !discovery.matching {
+        return;
+    }
+    if *state.current() != AppState::MatchingRandom {
+        discovery.matching = false;
+    } else if let Some(host) = discovery.hosts.first() {
+        let key = host.addr.to_string();
+        discovery.matching = false;
+        sender.0.push(HugCommand::JoinRoom { key });
+    }
+}
+
+pub fn discovery_input(
+    input: Res<Input<KeyCode>>,
+    discovery: Res<LanDiscovery>,
+    mut sender: ResMut<Sender>,
+    mut state: ResMut<State<AppState>>,
+    mut message: ResMut<Message>,
+    messages: Res<Messages>,
+) {
+    if *state.current() != AppState::Alone {
+        return;
+    }
+    if input.just_pressed(KeyCode::H) {
+        let _ = state.set(AppState::CreatingRoom);
+    } else if input.just_pressed(KeyCode::R) {
+        let _ = state.set(AppState::MatchingRandom);
+    } else if let Some(host) = JOIN_KEYS
+        .iter()
+        .position(|key| input.just_pressed(*key))
+        .and_then(|index| discovery.hosts.get(index))
+    {
+        message.0 = messages.finding_room.into();
+        sender.0.push(HugCommand::JoinRoom {
+            key: host.addr.to_string(),
+        });
+        let _ = state.set(AppState::MatchingByKey);
+    }
+}
+
+pub fn insert_discovery_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("DotGothic16-Regular.ttf"),
+                    font_size: 24.0,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(DiscoveryText);
+}
+
+pub fn update_discovery_text(
+    discovery: Res<LanDiscovery>,
+    state: Res<State<AppState>>,
+    policy: Res<NamePolicy>,
+    mut text: Query<&mut Text, With<DiscoveryText>>,
+) {
+    if let Ok(mut text) = text.single_mut() {
+        let value = if *state.current() == AppState::Alone {
+            let mut lines = vec!["H: host  R: random".to_string()];
+            for (i, host) in discovery.hosts.iter().take(JOIN_KEYS.len()).enumerate() {
+                // Broadcast by anyone on the network, like the partner's name.
+                lines.push(format!(
+                    "{}: {} @ {} ({})",
+                    i + 1,
+                    policy.sanitize(&host.player_name),
+                    policy.sanitize(&host.host_name),
+                    host.addr
+                ));
+            }
+            lines.join("\n")
+        } else {
+            String::new()
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
@@ -2,8 +2,11 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
 use crate::components::{
+    avatar::PlayerAvatar,
     body::part::*,
     control::HandControl,
+    grab::Grabs,
+    locomotion::Stance,
     networking::{HugCommand, IsPrimary, Payload, PlayerName, PushTimer, Sender, SyncTimer},
     player::{Player1, Player2},
 };
@@ -18,6 +21,11 @@ pub fn push_hand_control(
         let payload = Payload::HandControl {
             left: hand.left().clone(),
             right: hand.right().clone(),
+            grip: hand.grip().clone(),
+            torso: hand.torso().clone(),
+            look: hand.look(),
+            reach: hand.reach(),
+            assist: hand.assist(),
         };
         sender.0.push(HugCommand::Push { payload });
     }
@@ -70,11 +78,50 @@ pub fn sync_name(mut sender: ResMut<Sender>, name: Res<PlayerName<Player1>>) {
     }
 }
 
+pub fn sync_avatar(mut sender: ResMut<Sender>, avatar: Res<PlayerAvatar<Player1>>) {
+    if avatar.is_changed() {
+        sender.0.push(HugCommand::Push {
+            payload: Payload::Avatar(avatar.0.clone()),
+        });
+    }
+}
+
+pub fn sync_grabs(mut sender: ResMut<Sender>, grabs: Res<Grabs<Player1>>) {
+    if grabs.is_changed() {
+        sender.0.push(HugCommand::Push {
+            payload: Payload::Grab {
+                left: grabs.left.target(),
+                right: grabs.right.target(),
+            },
+        });
+    }
+}
+
+/// Sends the stance at most 20 times a second while walking.
+pub fn sync_stance(
+    mut sender: ResMut<Sender>,
+    stance: Res<Stance<Player1>>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut sent: Local<(Vec2, f32)>,
+) {
+    *elapsed += time.delta_seconds();
+    let current = (stance.offset(), stance.facing());
+    if *elapsed < 1.0 / 20.0 || *sent == current {
+        return;
+    }
+    *elapsed = 0.0;
+    *sent = current;
+    sender.0.push(HugCommand::Push {
+        payload: Payload::Stance {
+            offset: current.0,
+            facing: current.1,
+        },
+    });
+}
+
+/// Positions are sent in our own scene; the receiver maps them into theirs.
 fn position_to_vec(position: &RigidBodyPosition) -> Vec3 {
-    // rotate y axis 180 degrees
-    Vec3::new(
-        -position.position.translation.x,
-        position.position.translation.y,
-        -position.position.translation.z,
-    )
+    let translation = position.position.translation;
+    Vec3::new(translation.x, translation.y, translation.z)
 }
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use js_sys::Function;
 use wasm_bindgen::JsValue;
 
-use crate::components::networking::{Receiver, Sender};
+use crate::components::networking::{PeerViolations, Receiver, Sender, Violation};
 
 use super::BUFFER;
 
@@ -24,10 +24,16 @@ pub fn sender(mut sender: ResMut<Sender>) {
     }
 }
 
-pub fn receiver(mut receiver: ResMut<Receiver>) {
+pub fn receiver(mut receiver: ResMut<Receiver>, mut violations: ResMut<PeerViolations>) {
     let buffer = BUFFER.lock().split_off(0);
-    receiver.0 = buffer
-        .into_iter()
-        .map(|message| serde_json::from_str(&message).unwrap())
-        .collect();
+    receiver.0.clear();
+    for message in buffer {
+        match serde_json::from_str(&message) {
+            Ok(event) => receiver.0.push(event),
+            Err(err) => {
+                warn!("invalid message: {}", err);
+                violations.record(Violation::Malformed);
+            }
+        }
+    }
 }
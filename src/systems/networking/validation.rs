@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::components::{
+    avatar::{Avatar, MAX_SCALE, MIN_SCALE, PALETTE},
+    body::{part::BodyPartId, Pose},
+    control::{is_assist_in_range, is_in_range, is_look_in_range, is_torso_in_range},
+    grab::is_grabbable,
+    locomotion::{MAX_APPROACH, MAX_FACING, STANCE_RANGE},
+    name_policy::MAX_NAME_GRAPHEMES,
+    networking::Violation,
+    player::Placement,
+    pose::MAX_BLEND_SECONDS,
+};
+
+/// How far a part gets from where its player stands, beyond the stance, even falling over.
+const MAX_REACH: f32 = 2.5;
+const MIN_HEIGHT: f32 = -1.0;
+const MAX_HEIGHT: f32 = 4.0;
+/// How far a synced part may be from where we simulate it. Corrections are also moved at most
+/// this far at once, so diverged simulations converge again without teleporting parts through
+/// each other.
+const MAX_SYNC_DISPLACEMENT: f32 = 1.0;
+/// Parts are never this far from their parents, even on the tallest avatars.
+const MAX_POSE_OFFSET: f32 = 1.5;
+
+pub fn validate_hand_control(
+    left: &Vec2,
+    right: &Vec2,
+    grip: &Vec2,
+    torso: &Vec3,
+    look: &Option<Vec2>,
+    reach: &Option<Vec2>,
+    assist: f32,
+) -> Result<(), Violation> {
+    if !left.is_finite()
+        || !right.is_finite()
+        || !grip.is_finite()
+        || !torso.is_finite()
+        || !look.map_or(true, |look| look.is_finite())
+        || !reach.map_or(true, |reach| reach.is_finite())
+        || !assist.is_finite()
+    {
+        return Err(Violation::NonFinite);
+    }
+    if !is_in_range(left)
+        || !is_in_range(right)
+        || !is_in_range(grip)
+        || !is_torso_in_range(torso)
+        || !look.map_or(true, |look| is_look_in_range(&look))
+        || !reach.map_or(true, |reach| is_in_range(&reach))
+        || !is_assist_in_range(assist)
+    {
+        // Still applied, because the setters clamp.
+        return Err(Violation::OutOfRange);
+    }
+    Ok(())
+}
+
+/// `next` is a part of the player placed at `placement`.
+pub fn validate_position<T>(next: &Vec3, placement: &Placement<T>) -> Result<(), Violation> {
+    if !next.is_finite() {
+        return Err(Violation::NonFinite);
+    }
+    let offset = *next - placement.translation;
+    if Vec2::new(offset.x, offset.z).length() > STANCE_RANGE + MAX_REACH
+        || offset.y < MIN_HEIGHT
+        || offset.y > MAX_HEIGHT
+    {
+        return Err(Violation::OutOfBounds);
+    }
+    Ok(())
+}
+
+pub fn validate_displacement(current: &Vec3, next: &Vec3) -> Result<(), Violation> {
+    if current.distance(*next) > MAX_SYNC_DISPLACEMENT {
+        return Err(Violation::Teleport);
+    }
+    Ok(())
+}
+
+/// Where a part at `current` moves toward a valid correction `next`.
+pub fn limit_correction(current: Vec3, next: Vec3) -> Vec3 {
+    let correction = next - current;
+    let distance = correction.length();
+    if distance > MAX_SYNC_DISPLACEMENT {
+        current + correction * (MAX_SYNC_DISPLACEMENT / distance)
+    } else {
+        next
+    }
+}
+
+/// Names are sent sanitized, so never longer than the policy allows.
+pub fn validate_name(name: &str) -> Result<(), Violation> {
+    if name.graphemes(true).count() > MAX_NAME_GRAPHEMES {
+        return Err(Violation::NameTooLong);
+    }
+    Ok(())
+}
+
+pub fn validate_pose(pose: &Pose, seconds: f32) -> Result<(), Violation> {
+    let finite = pose
+        .0
+        .iter()
+        .all(|part| part.translation.is_finite() && part.rotation.is_finite());
+    if !finite || !seconds.is_finite() {
+        return Err(Violation::NonFinite);
+    }
+    let in_range = pose
+        .0
+        .iter()
+        .all(|part| part.translation.length() <= MAX_POSE_OFFSET && part.rotation.is_normalized());
+    if !in_range || !(0.0..=MAX_BLEND_SECONDS).contains(&seconds) {
+        return Err(Violation::OutOfRange);
+    }
+    Ok(())
+}
+
+pub fn validate_avatar(avatar: &Avatar) -> Result<(), Violation> {
+    if !avatar.height.is_finite() || !avatar.proportion.is_finite() {
+        return Err(Violation::NonFinite);
+    }
+    let scales = MIN_SCALE..=MAX_SCALE;
+    if avatar.color as usize >= PALETTE.len()
+        || !scales.contains(&avatar.height)
+        || !scales.contains(&avatar.proportion)
+    {
+        return Err(Violation::OutOfRange);
+    }
+    Ok(())
+}
+
+pub fn validate_grab(target: Option<BodyPartId>) -> Result<(), Violation> {
+    match target {
+        Some(part) if !is_grabbable(part) => Err(Violation::OutOfRange),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_stance(offset: &Vec2, facing: f32) -> Result<(), Violation> {
+    if !offset.is_finite() || !facing.is_finite() {
+        return Err(Violation::NonFinite);
+    }
+    // Still applied, because `Stance::set` clamps.
+    if offset.x.abs() > STANCE_RANGE
+        || !(-STANCE_RANGE..=MAX_APPROACH).contains(&-offset.y)
+        || facing.abs() > MAX_FACING
+    {
+        return Err(Violation::OutOfRange);
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use parking_lot::Mutex;
+
+use crate::components::{
+    assist::ReachAssist,
+    body::PlayerBody,
+    control::HandControl,
+    idle::IdleAnimation,
+    look::HeadLook,
+    networking::{HugCommand, Payload, Sender},
+    player::Player1,
+    pose::{PoseBlend, PoseCommand, PoseLibrary, DEFAULT_BLEND_SECONDS},
+    state::AppState,
+};
+
+lazy_static::lazy_static! {
+    pub static ref POSE_COMMANDS: Mutex<Vec<PoseCommand>> = Mutex::new(Vec::new());
+}
+
+pub const REST_POSE: &str = "rest";
+
+/// `P` blends into the next saved pose, `Shift+P` saves the current one and `O` rests.
+pub fn pose_input(input: Res<Input<KeyCode>>, library: Res<PoseLibrary>, mut next: Local<usize>) {
+    let mut commands = POSE_COMMANDS.lock();
+    let shift = input.pressed(KeyCode::LShift) || input.pressed(KeyCode::RShift);
+    if input.just_pressed(KeyCode::P) && shift {
+        let name = (1..)
+            .map(|n| format!("pose {}", n))
+            .find(|name| !library.poses.contains_key(name))
+            .unwrap();
+        commands.push(PoseCommand::Save { name });
+    } else if input.just_pressed(KeyCode::P) {
+        if let Some(name) = library.poses.keys().cycle().nth(*next) {
+            commands.push(PoseCommand::Apply {
+                name: name.clone(),
+                seconds: DEFAULT_BLEND_SECONDS,
+            });
+            *next = (*next + 1) % library.poses.len();
+        }
+    }
+    if input.just_pressed(KeyCode::O) {
+        commands.push(PoseCommand::Apply {
+            name: REST_POSE.into(),
+            seconds: DEFAULT_BLEND_SECONDS,
+        });
+    }
+}
+
+pub fn handle_pose_commands(
+    mut library: ResMut<PoseLibrary>,
+    mut blend: ResMut<PoseBlend<Player1>>,
+    body: Res<PlayerBody<Player1>>,
+    control: Res<HandControl<Player1>>,
+    assist: Res<ReachAssist<Player1>>,
+    idle: Res<IdleAnimation<Player1>>,
+    look: Res<HeadLook<Player1>>,
+    mut sender: ResMut<Sender>,
+    state: Res<State<AppState>>,
+) {
+    for command in POSE_COMMANDS.lock().drain(..) {
+        match command {
+            PoseCommand::Save { name } => {
+                // Peel off the layers in the reverse order of `apply_layers`, so applying the
+                // pose doesn't add them twice.
+                let mut pose = body.relative.pose();
+                let layers: Vec<_> = idle
+                    .layer()
+                    .iter()
+                    .chain(control.layer(&body.relative, assist.torso()).iter())
+                    .chain(look.layer().iter())
+                    .copied()
+                    .collect();
+                for (part, rotation) in layers.iter().rev() {
+                    let part = &mut pose.0[part.index()];
+                    part.rotation = part.rotation * rotation.inverse();
+                }
+                if let Some((root, transform)) = body.relative.iter().next() {
+                    pose.0[root.index()].translation -= transform.rotation * idle.shift();
+                }
+                info!("saved the pose \"{}\"", name);
+                library.poses.insert(name, pose);
+                library.save();
+            }
+            PoseCommand::Apply { name, seconds } => {
+                let pose = match library.poses.get(&name) {
+                    Some(pose) => pose.clone(),
+                    None if name == REST_POSE => blend.rest.clone(),
+                    None => {
+                        warn!("no pose named \"{}\"", name);
+                        continue;
+                    }
+                };
+                blend.blend_to(pose.clone(), seconds);
+                if *state.current() == AppState::Connected {
+                    sender.0.push(HugCommand::Push {
+                        payload: Payload::Pose { pose, seconds },
+                    });
+                }
+            }
+            PoseCommand::Delete { name } => {
+                if library.poses.remove(&name).is_some() {
+                    library.save();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::components::{
+    avatar::PlayerAvatar,
+    body::part::Head,
+    control::HandControl,
+    name_policy::NamePolicy,
+    networking::PlayerName,
+    player::{Player1, Player2},
+    replay::{Replay, ReplayFrame, ReplayPlayback, ReplayRecorder, REPLAY_RATE},
+    state::AppState,
+    ui::{Alert, Message, Messages},
+};
+
+pub fn load_replay(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    mut alert: ResMut<Alert>,
+    messages: Res<Messages>,
+) {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let replay = unsafe { js_sys::Reflect::get(&document, &"replay".into()).unwrap() };
+    if let Some(text) = replay.as_string() {
+        match Replay::decode(&text) {
+            Ok(replay) if !replay.frames.is_empty() => {
+                commands.insert_resource(ReplayPlayback {
+                    replay,
+                    elapsed: Duration::default(),
+                });
+                let _ = state.set(AppState::Replaying);
+            }
+            _ => {
+                alert.0 = messages.replay_invalid.into();
+            }
+        }
+    }
+}
+
+pub fn start_recording(mut recorder: ResMut<ReplayRecorder>) {
+    *recorder = ReplayRecorder::default();
+}
+
+pub fn record_replay(
+    mut recorder: ResMut<ReplayRecorder>,
+    time: Res<Time>,
+    control1: Res<HandControl<Player1>>,
+    control2: Res<HandControl<Player2>>,
+) {
+    if recorder.timer.tick(time.delta()).just_finished() {
+        let frame = ReplayFrame::new(
+            (*control1.left(), *control1.right()),
+            (*control2.left(), *control2.right()),
+        );
+        recorder.push(frame);
+    }
+}
+
+pub fn on_replay_started(
+    playback: Res<ReplayPlayback>,
+    policy: Res<NamePolicy>,
+    mut name1: ResMut<PlayerName<Player1>>,
+    mut name2: ResMut<PlayerName<Player2>>,
+    mut message: ResMut<Message>,
+    messages: Res<Messages>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    avatar: Res<PlayerAvatar<Player2>>,
+    head: Query<&Handle<StandardMaterial>, (With<Player2>, With<Head>)>,
+) {
+    name1.0 = policy.sanitize(&playback.replay.player1_name);
+    name2.0 = policy.sanitize(&playback.replay.player2_name);
+    message.0 = messages.replaying.into();
+    // Players rendered by a rig have no material.
+    if let Some(material) = head.single().ok().and_then(|head| materials.get_mut(head)) {
+        material.unlit = false;
+        material.base_color = avatar.0.color();
+    }
+}
+
+pub fn play_replay(
+    time: Res<Time>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut control1: ResMut<HandControl<Player1>>,
+    mut control2: ResMut<HandControl<Player2>>,
+) {
+    let duration = playback.replay.duration();
+    playback.elapsed += time.delta();
+    // Loop the clip so it can be watched more than once.
+    if playback.elapsed >= duration {
+        playback.elapsed =
+            Duration::from_secs_f32(playback.elapsed.as_secs_f32() % duration.as_secs_f32());
+    }
+
+    let position = playback.elapsed.as_secs_f32() * REPLAY_RATE as f32;
+    let frames = &playback.replay.frames;
+    let index = (position as usize).min(frames.len() - 1);
+    let next = (index + 1).min(frames.len() - 1);
+    let frame = frames[index].lerp(&frames[next], position.fract());
+
+    let (left, right) = frame.player1();
+    control1.set_left(left);
+    control1.set_right(right);
+    let (left, right) = frame.player2();
+    control2.set_left(left);
+    control2.set_right(right);
+}
+
+pub fn replay_url(
+    recorder: &ReplayRecorder,
+    name1: &PlayerName<Player1>,
+    name2: &PlayerName<Player2>,
+) -> String {
+    let replay = Replay {
+        player1_name: name1.0.clone(),
+        player2_name: name2.0.clone(),
+        frames: recorder.frames.iter().copied().collect(),
+    };
+    format!("?replay={}", replay.encode())
+}
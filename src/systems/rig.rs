@@ -0,0 +1,155 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::components::{
+    body::{part::BodyPartId, PlayerBody},
+    player::{Placement, Player, Player1, Player2},
+    rig::{RigBone, RigBound, RigConfig, RigRoot},
+};
+
+pub fn spawn_rig(
+    mut commands: Commands,
+    config: Res<RigConfig>,
+    asset_server: Res<AssetServer>,
+    placements: (Res<Placement<Player1>>, Res<Placement<Player2>>),
+) {
+    let scene: Handle<Scene> = asset_server.load(config.scene.as_str());
+    // Characters face +z, and bodies face -z.
+    let turned = Transform::from_rotation(Quat::from_rotation_y(PI));
+    spawn_root::<Player1>(
+        &mut commands,
+        scene.clone(),
+        placements.0.transform() * turned,
+        config.scale,
+    );
+    spawn_root::<Player2>(
+        &mut commands,
+        scene,
+        placements.1.transform() * turned,
+        config.scale,
+    );
+}
+
+fn spawn_root<T: Player>(
+    commands: &mut Commands,
+    scene: Handle<Scene>,
+    transform: Transform,
+    scale: f32,
+) {
+    commands
+        .spawn_bundle((
+            transform.with_scale(Vec3::splat(scale)),
+            GlobalTransform::identity(),
+        ))
+        .insert(RigRoot)
+        .insert(T::default())
+        .with_children(|parent| {
+            parent.spawn_scene(scene);
+        });
+}
+
+/// Finds the bones of the mapping once the scene is spawned, and records their rest rotations.
+pub fn bind_rig<T: Player>(
+    mut commands: Commands,
+    config: Res<RigConfig>,
+    roots: Query<
+        (Entity, &Transform, Option<&Children>),
+        (With<RigRoot>, With<T>, Without<RigBound>),
+    >,
+    nodes: Query<(Option<&Name>, &Transform, Option<&Children>)>,
+    parts: Query<(&BodyPartId, &RigidBodyPositionComponent), With<T>>,
+) {
+    let (root, transform, children) = match roots.single() {
+        Ok((root, transform, Some(children))) => (root, transform, children),
+        _ => return,
+    };
+    let mut rotations = HashMap::new();
+    for (part, position) in parts.iter() {
+        rotations.insert(*part, to_quat(&position.0.position.rotation));
+    }
+
+    // Global transforms are computed here, because the scene may not be propagated yet.
+    let mut stack: Vec<(Entity, Transform)> = children.iter().map(|c| (*c, *transform)).collect();
+    let mut found = 0;
+    while let Some((entity, parent)) = stack.pop() {
+        let (name, local, children) = match nodes.get(entity) {
+            Ok(node) => node,
+            Err(_) => continue,
+        };
+        let global = parent * *local;
+        if let Some(part) = name.and_then(|name| config.bones.get(name.as_str())) {
+            let rotation = rotations.get(part).copied().unwrap_or(Quat::IDENTITY);
+            commands
+                .entity(entity)
+                .insert(T::default())
+                .insert(RigBone {
+                    part: *part,
+                    rest: rotation.inverse() * global.rotation,
+                    scale: global.scale,
+                });
+            found += 1;
+        }
+        if let Some(children) = children {
+            stack.extend(children.iter().map(|c| (*c, global)));
+        }
+    }
+    if found == 0 {
+        // The scene is not spawned yet.
+        return;
+    }
+    if found < config.bones.len() {
+        warn!(
+            "{} bones of the rig are missing",
+            config.bones.len() - found
+        );
+    }
+    commands.entity(root).insert(RigBound);
+}
+
+/// Places the bones where the physics bodies are.
+pub fn drive_rig<T: Player>(
+    body: Res<PlayerBody<T>>,
+    parts: Query<(&BodyPartId, &RigidBodyPositionComponent), With<T>>,
+    mut bones: Query<(Entity, &RigBone, &Parent, &mut Transform), With<T>>,
+    globals: Query<&GlobalTransform>,
+) {
+    let mut positions = HashMap::new();
+    for (part, position) in parts.iter() {
+        positions.insert(*part, position.0.position);
+    }
+    let mut desired = HashMap::new();
+    for (entity, bone, _, _) in bones.iter() {
+        if let Some(position) = positions.get(&bone.part) {
+            // The bone starts at the joint to the parent part.
+            let start = match body.relative.parent(bone.part) {
+                Some(_) => -body.relative.part(bone.part).translation,
+                None => Vec3::ZERO,
+            };
+            let start = position * point!(start.x, start.y, start.z);
+            let rotation = to_quat(&position.rotation);
+            desired.insert(
+                entity,
+                Mat4::from_scale_rotation_translation(
+                    bone.scale,
+                    rotation * bone.rest,
+                    Vec3::new(start.x, start.y, start.z),
+                ),
+            );
+        }
+    }
+    for (entity, _, parent, mut transform) in bones.iter_mut() {
+        let parent_global = desired
+            .get(&parent.0)
+            .copied()
+            .or_else(|| globals.get(parent.0).ok().map(|g| g.compute_matrix()));
+        if let (Some(global), Some(parent_global)) = (desired.get(&entity), parent_global) {
+            *transform = Transform::from_matrix(parent_global.inverse() * *global);
+        }
+    }
+}
+
+fn to_quat(rotation: &Rotation<f32>) -> Quat {
+    Quat::from_xyzw(rotation.i, rotation.j, rotation.k, rotation.w)
+}
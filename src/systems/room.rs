@@ -0,0 +1,163 @@
+use std::sync::mpsc::TryRecvError;
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::components::{
+    avatar::PlayerAvatar,
+    body::part::*,
+    control::HandControl,
+    grab::{Grab, Grabs},
+    locomotion::Stance,
+    networking::{PartState, Payload},
+    player::{partner_to_players, Placement, Player1, Player2},
+    pose::PoseBlend,
+    room::{BroadcastTimer, RoomInput, RoomLink, RoomOutput, Seat},
+};
+
+pub fn apply_input(
+    link: Res<RoomLink>,
+    mut control1: ResMut<HandControl<Player1>>,
+    mut control2: ResMut<HandControl<Player2>>,
+    mut pose1: ResMut<PoseBlend<Player1>>,
+    mut pose2: ResMut<PoseBlend<Player2>>,
+    mut avatars: (ResMut<PlayerAvatar<Player1>>, ResMut<PlayerAvatar<Player2>>),
+    mut grabs: (ResMut<Grabs<Player1>>, ResMut<Grabs<Player2>>),
+    mut stances: (ResMut<Stance<Player1>>, ResMut<Stance<Player2>>),
+    mut exit: EventWriter<AppExit>,
+) {
+    let input = link.input.lock();
+    loop {
+        match input.try_recv() {
+            Ok(RoomInput::HandControl {
+                seat,
+                left,
+                right,
+                grip,
+                torso,
+                look,
+                reach,
+                assist,
+            }) => match seat {
+                Seat::First => {
+                    control1.set_left(left);
+                    control1.set_right(right);
+                    control1.set_grip(grip);
+                    control1.set_torso(torso);
+                    control1.set_look(look);
+                    control1.set_reach(reach);
+                    control1.set_assist(assist);
+                }
+                Seat::Second => {
+                    control2.set_left(left);
+                    control2.set_right(right);
+                    control2.set_grip(grip);
+                    control2.set_torso(torso);
+                    control2.set_look(look);
+                    control2.set_reach(reach);
+                    control2.set_assist(assist);
+                }
+            },
+            Ok(RoomInput::Pose {
+                seat,
+                pose,
+                seconds,
+            }) => match seat {
+                Seat::First => pose1.blend_to(pose, seconds),
+                Seat::Second => pose2.blend_to(pose, seconds),
+            },
+            Ok(RoomInput::Avatar { seat, avatar }) => match seat {
+                Seat::First => avatars.0 .0 = avatar,
+                Seat::Second => avatars.1 .0 = avatar,
+            },
+            Ok(RoomInput::Grab { seat, left, right }) => {
+                let (left, right) = (Grab::from_target(left), Grab::from_target(right));
+                match seat {
+                    Seat::First => {
+                        grabs.0.left = left;
+                        grabs.0.right = right;
+                    }
+                    Seat::Second => {
+                        grabs.1.left = left;
+                        grabs.1.right = right;
+                    }
+                }
+            }
+            // Stances are relative to where each player spawned.
+            Ok(RoomInput::Stance {
+                seat,
+                offset,
+                facing,
+            }) => match seat {
+                Seat::First => stances.0.set(offset, facing),
+                Seat::Second => stances.1.set(offset, facing),
+            },
+            Err(TryRecvError::Empty) => break,
+            // The lobby closed the room.
+            Err(TryRecvError::Disconnected) => {
+                exit.send(AppExit);
+                break;
+            }
+        }
+    }
+}
+
+pub fn broadcast_state(
+    link: Res<RoomLink>,
+    mut timer: ResMut<BroadcastTimer>,
+    time: Res<Time>,
+    placements: (Res<Placement<Player1>>, Res<Placement<Player2>>),
+    player1: Query<(&BodyPartId, &RigidBodyPositionComponent), With<Player1>>,
+    player2: Query<(&BodyPartId, &RigidBodyPositionComponent), With<Player2>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let player1: Vec<_> = player1
+        .iter()
+        .map(|(part, position)| to_part_state(*part, &position.0))
+        .collect();
+    let player2: Vec<_> = player2
+        .iter()
+        .map(|(part, position)| to_part_state(*part, &position.0))
+        .collect();
+    let output = link.output.lock();
+    // Receivers take a state as if their partner sent it from their own scene, and map it with
+    // `partner_to_players`. The second seat's partner is player1, so it gets the room as is.
+    let _ = output.send(RoomOutput {
+        seat: Seat::Second,
+        payload: Payload::State {
+            player1: player1.clone(),
+            player2: player2.clone(),
+        },
+    });
+    // The first seat's partner is player2, which stands at player1's place in its own scene.
+    // Mapping each body the opposite way of the receiver leaves it where we simulate it.
+    let (to_player1, to_player2) = partner_to_players(&placements.0, &placements.1);
+    let (to_player1, to_player2) = (
+        Transform::from_matrix(to_player1),
+        Transform::from_matrix(to_player2),
+    );
+    let _ = output.send(RoomOutput {
+        seat: Seat::First,
+        payload: Payload::State {
+            player1: player2
+                .iter()
+                .map(|state| state.mapped(&to_player1))
+                .collect(),
+            player2: player1
+                .iter()
+                .map(|state| state.mapped(&to_player2))
+                .collect(),
+        },
+    });
+}
+
+fn to_part_state(part: BodyPartId, position: &RigidBodyPosition) -> PartState {
+    let (translation, rotation) = (position.position.translation, position.position.rotation);
+    PartState {
+        part,
+        translation: Vec3::new(translation.x, translation.y, translation.z),
+        rotation: Quat::from_xyzw(rotation.i, rotation.j, rotation.k, rotation.w),
+    }
+}
@@ -3,16 +3,29 @@ use std::f32::consts::PI;
 use bevy::prelude::{shape as bevy_shape, *};
 use bevy_rapier3d::prelude::*;
 
+pub fn setup_physics(mut commands: Commands, mut integration: ResMut<IntegrationParameters>) {
+    integration.max_velocity_iterations = 5;
+    integration.max_stabilization_iterations = 2;
+
+    // ground
+    commands
+        .spawn_bundle(RigidBodyBundle {
+            position: vector!(0.0, -0.1, 0.0).into(),
+            body_type: RigidBodyType::Static.into(),
+            ..Default::default()
+        })
+        .insert_bundle(ColliderBundle {
+            shape: ColliderShape::cuboid(10.0, 0.1, 10.0).into(),
+            ..Default::default()
+        });
+}
+
 pub fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     // asset_server: Res<AssetServer>,
-    mut integration: ResMut<IntegrationParameters>,
 ) {
-    integration.max_velocity_iterations = 5;
-    integration.max_stabilization_iterations = 2;
-
     let wall_material = materials.add(StandardMaterial {
         base_color: Color::rgb(0.78, 0.73, 0.75),
         roughness: 0.3,
@@ -21,21 +34,12 @@ pub fn setup(
         ..Default::default()
     });
     // plane
-    commands
-        .spawn_bundle(PbrBundle {
-            mesh: meshes.add(Mesh::from(bevy_shape::Plane { size: 7.0 })),
-            material: wall_material.clone(),
-            ..Default::default()
-        })
-        .insert_bundle(RigidBodyBundle {
-            position: vector!(0.0, -0.1, 0.0).into(),
-            body_type: RigidBodyType::Static.into(),
-            ..Default::default()
-        })
-        .insert_bundle(ColliderBundle {
-            shape: ColliderShape::cuboid(10.0, 0.1, 10.0).into(),
-            ..Default::default()
-        });
+    commands.spawn_bundle(PbrBundle {
+        transform: Transform::from_xyz(0.0, -0.1, 0.0),
+        mesh: meshes.add(Mesh::from(bevy_shape::Plane { size: 7.0 })),
+        material: wall_material.clone(),
+        ..Default::default()
+    });
 
     // wall left
     commands.spawn_bundle(PbrBundle {
@@ -1,200 +1,288 @@
-use bevy::{
-    ecs::component::Component,
-    prelude::{shape as bshape, *},
-};
+use std::collections::HashMap;
+
+use bevy::prelude::{shape as bshape, *};
 use bevy_rapier3d::{na::Vector3, prelude::*};
 
-use crate::components::{
-    body::{part::*, *},
-    control::HandControl,
-    physics::{CollisionTag, Joint, JointMotorParams},
-    player::{Player, Player1, Player2},
-    ragdoll::BallonForceCoef,
+use crate::{
+    components::{
+        avatar::{Avatar, PlayerAvatar},
+        body::{part::BodyPartId, *},
+        control::HandControl,
+        idle::IdleAnimation,
+        locomotion::{ground, FootPlant, Stance, HIP_DAMPING, HIP_STIFFNESS},
+        physics::{CollisionTag, GroundAnchor, Joint, JointMotorParams},
+        player::{Placement, Player, Player1, Player2, PlayerId},
+        pose::PoseBlend,
+        ragdoll::BallonForceCoef,
+        rig::RigConfig,
+        rounded_box::RoundedBox,
+        skeleton::{Anchor, JointDef, PartDef, Shape, Skeleton},
+    },
+    systems::locomotion::to_isometry,
 };
 
-pub const PLAYER2_COLOR: Color = Color::rgb(0.2, 0.2, 0.7);
-
 pub fn setup_player(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    skeleton: Res<Skeleton>,
+    avatars: (Res<PlayerAvatar<Player1>>, Res<PlayerAvatar<Player2>>),
+    placements: (Res<Placement<Player1>>, Res<Placement<Player2>>),
+    rig: Option<Res<RigConfig>>,
 ) {
-    create_player::<Player1>(
+    let (avatar1, avatar2) = avatars;
+    let spawned1 = create_player::<Player1>(
         &mut commands,
-        &mut meshes,
-        &mut materials,
-        Body::player1(),
-        Transform::from_translation(Vec3::new(0.0, 0.0, 0.2)),
-        Color::rgb(0.6, 0.4, 0.1),
+        &avatar1.0.apply(&skeleton),
+        placements.0.transform(),
     );
+    let spawned2 = create_player::<Player2>(
+        &mut commands,
+        &avatar2.0.apply(&skeleton),
+        placements.1.transform(),
+    );
+    // A rig renders the bodies instead.
+    if rig.is_none() {
+        let material = body_material(&mut materials, avatar1.0.color());
+        insert_meshes(&mut commands, &mut meshes, material, spawned1);
+        let material = body_material(&mut materials, avatar2.0.color());
+        insert_meshes(&mut commands, &mut meshes, material, spawned2);
+    }
+}
 
+/// Same ragdolls as `setup_player`, without anything to render.
+pub fn setup_headless_player(
+    mut commands: Commands,
+    skeleton: Res<Skeleton>,
+    avatars: (Res<PlayerAvatar<Player1>>, Res<PlayerAvatar<Player2>>),
+    placements: (Res<Placement<Player1>>, Res<Placement<Player2>>),
+) {
+    let (avatar1, avatar2) = avatars;
+    create_player::<Player1>(
+        &mut commands,
+        &avatar1.0.apply(&skeleton),
+        placements.0.transform(),
+    );
     create_player::<Player2>(
         &mut commands,
-        &mut meshes,
-        &mut materials,
-        Body::player2(),
-        Transform::from_translation(Vec3::new(0.0, 0.0, -0.2)),
-        PLAYER2_COLOR,
+        &avatar2.0.apply(&skeleton),
+        placements.1.transform(),
     );
 }
 
-fn create_player<T: Player + Copy>(
-    commands: &mut Commands,
-    meshes: &mut Assets<Mesh>,
-    materials: &mut Assets<StandardMaterial>,
-    mut body: Body,
-    transform: Transform,
-    color: Color,
+/// Respawns the ragdoll of a player whose avatar changed shape, where it stands, or recolors it.
+/// The pose, hand control and idle motion carry over.
+pub fn rebuild_body<T: Player>(
+    mut commands: Commands,
+    avatar: Res<PlayerAvatar<T>>,
+    skeleton: Res<Skeleton>,
+    stance: Res<Stance<T>>,
+    idle: Res<IdleAnimation<T>>,
+    mut blend: ResMut<PoseBlend<T>>,
+    mut built: Local<Option<Avatar>>,
+    entities: Query<
+        Entity,
+        (
+            With<T>,
+            Or<(With<BodyPartId>, With<Joint>, With<GroundAnchor>)>,
+        ),
+    >,
+    material: Query<&Handle<StandardMaterial>, (With<T>, With<BodyPartId>)>,
+    assets: (
+        Option<ResMut<Assets<Mesh>>>,
+        Option<ResMut<Assets<StandardMaterial>>>,
+    ),
 ) {
-    *body.get_mut::<Hip>() = transform * *body.get::<Hip>();
+    // The body spawned at startup is built from the avatar already.
+    let built = built.get_or_insert_with(|| avatar.0.clone());
+    if *built == avatar.0 {
+        return;
+    }
+    let same_shape = built.same_shape(&avatar.0);
+    *built = avatar.0.clone();
 
-    let body = PlayerBody::<T>::new(body.clone(), body.propagated());
+    let material = material.iter().next().cloned();
+    if same_shape {
+        // Only recolored, so the ragdoll and any grab keep going.
+        if let (Some(mut materials), Some(material)) = (assets.1, material) {
+            if let Some(material) = materials.get_mut(&material).filter(|m| !m.unlit) {
+                material.base_color = avatar.0.color();
+            }
+        }
+        return;
+    }
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    let (mut body, spawned) = spawn_body::<T>(
+        &mut commands,
+        &avatar.0.apply(&skeleton),
+        stance.transform(),
+    );
+    // The idle weight shift is applied to the root already.
+    let root = body.relative.root_mut();
+    root.translation += root.rotation * idle.shift();
+    body.absolute = body.relative.propagated();
+    blend.rest = body.relative.pose();
+    commands.insert_resource(body);
+
+    // Bodies without a material are headless or rendered by a rig.
+    if let (Some(mut meshes), Some(mut materials), Some(material)) = (assets.0, assets.1, material)
+    {
+        // The absent partner keeps its unlit placeholder color.
+        if let Some(material) = materials.get_mut(&material).filter(|m| !m.unlit) {
+            material.base_color = avatar.0.color();
+        }
+        insert_meshes(&mut commands, &mut meshes, material, spawned);
+    }
+}
+
+/// Parts that have meshes, and their meshes.
+struct SpawnedBody(Vec<(Entity, Mesh)>);
+
+fn create_player<T: Player>(
+    commands: &mut Commands,
+    skeleton: &Skeleton,
+    transform: Transform,
+) -> SpawnedBody {
+    let (body, spawned) = spawn_body::<T>(commands, skeleton, transform);
     let control = HandControl::<T>::default();
+    let pose = PoseBlend::<T>::new(body.relative.pose());
+    // Player2 breathes half a breath later.
+    let idle = IdleAnimation::<T>::new(if T::id() == PlayerId::Player2 {
+        2.0
+    } else {
+        0.0
+    });
+    let stance = Stance::<T>::new(transform);
+
+    // PlayerBody
+    commands.insert_resource(body);
+    commands.insert_resource(control);
+    commands.insert_resource(pose);
+    commands.insert_resource(idle);
+    commands.insert_resource(stance);
 
-    let hip = spawn_body_part::<T, Hip>(commands, &body, small_collider::<T>);
-    let spine = spawn_body_part::<T, Spine>(commands, &body, torso_collider::<T>);
-    let chest = spawn_body_part::<T, Chest>(commands, &body, torso_collider::<T>);
-    let neck = spawn_body_part::<T, Neck>(commands, &body, neck_collider::<T>);
-    let head = spawn_body_part::<T, Head>(commands, &body, head_collider::<T>);
-    let upper_arm_left = spawn_body_part::<T, UpperArmLeft>(commands, &body, small_collider::<T>);
-    let forearm_left = spawn_body_part::<T, ForearmLeft>(commands, &body, arm_collider::<T>);
-    let hand_left = spawn_body_part::<T, HandLeft>(commands, &body, arm_collider::<T>);
-    let upper_arm_right = spawn_body_part::<T, UpperArmRight>(commands, &body, small_collider::<T>);
-    let forearm_right = spawn_body_part::<T, ForearmRight>(commands, &body, arm_collider::<T>);
-    let hand_right = spawn_body_part::<T, HandRight>(commands, &body, arm_collider::<T>);
-    let thigh_left = spawn_body_part::<T, ThighLeft>(commands, &body, small_collider::<T>);
-    let shin_left = spawn_body_part::<T, ShinLeft>(commands, &body, leg_collider::<T>);
-    let foot_left = spawn_body_part::<T, FootLeft>(commands, &body, leg_collider::<T>);
-    let thigh_right = spawn_body_part::<T, ThighRight>(commands, &body, small_collider::<T>);
-    let shin_right = spawn_body_part::<T, ShinRight>(commands, &body, leg_collider::<T>);
-    let foot_right = spawn_body_part::<T, FootRight>(commands, &body, leg_collider::<T>);
+    spawned
+}
 
-    joint::<Hip, Spine, _>(commands, &body, hip, spine);
-    joint::<Spine, Chest, _>(commands, &body, spine, chest);
-    joint::<Chest, Neck, _>(commands, &body, chest, neck);
-    joint::<Neck, Head, _>(commands, &body, neck, head);
-    joint::<Hip, ThighLeft, _>(commands, &body, hip, thigh_left);
-    joint::<ThighLeft, ShinLeft, _>(commands, &body, thigh_left, shin_left);
-    joint::<ShinLeft, FootLeft, _>(commands, &body, shin_left, foot_left);
-    joint::<Hip, ThighRight, _>(commands, &body, hip, thigh_right);
-    joint::<ThighRight, ShinRight, _>(commands, &body, thigh_right, shin_right);
-    joint::<ShinRight, FootRight, _>(commands, &body, shin_right, foot_right);
-    joint::<Chest, UpperArmLeft, _>(commands, &body, chest, upper_arm_left);
-    joint::<UpperArmLeft, ForearmLeft, _>(commands, &body, upper_arm_left, forearm_left);
-    joint::<ForearmLeft, HandLeft, _>(commands, &body, forearm_left, hand_left);
-    joint::<Chest, UpperArmRight, _>(commands, &body, chest, upper_arm_right);
-    joint::<UpperArmRight, ForearmRight, _>(commands, &body, upper_arm_right, forearm_right);
-    joint::<ForearmRight, HandRight, _>(commands, &body, forearm_right, hand_right);
+fn spawn_body<T: Player>(
+    commands: &mut Commands,
+    skeleton: &Skeleton,
+    transform: Transform,
+) -> (PlayerBody<T>, SpawnedBody) {
+    let mut body = Body::from_skeleton(skeleton);
+    *body.root_mut() = transform * *body.root_mut();
+    let body = PlayerBody::<T>::new(body.clone(), body.propagated());
 
-    // Lock hip's rotation by connecting to the ground (locking with mass properties causes panic).
+    // Anchors connect parts to the ground (locking with mass properties causes panic).
+    // The ground moves with the stance of the player.
+    let ground_transform = ground(&transform);
+    let to_ground = ground_transform.compute_matrix().inverse();
     let ground = commands
         .spawn_bundle(RigidBodyBundle {
-            position: vector!(0.0, -0.5, 0.0).into(),
-            body_type: RigidBodyType::Static.into(),
+            position: to_isometry(&ground_transform).into(),
+            body_type: RigidBodyType::KinematicPositionBased.into(),
             ..Default::default()
         })
         .insert_bundle(ColliderBundle {
             shape: ColliderShape::cuboid(0.1, 0.1, 0.1).into(),
             ..Default::default()
         })
+        .insert(T::default())
+        .insert(GroundAnchor)
         .id();
-    let joint = JointData::new(JointAxesMask::ANG_X | JointAxesMask::ANG_Y | JointAxesMask::ANG_Z);
-    commands
-        .spawn()
-        .insert(JointBuilderComponent::new(joint, ground, hip));
-    let trans = body.absolute.get::<FootLeft>().translation;
-    let joint = SphericalJoint::new()
-        .local_anchor1(point!(trans.x, trans.y + 0.5, trans.z))
-        .local_anchor2(point!(0.0, 0.0, 0.0));
-    commands
-        .spawn()
-        .insert(JointBuilderComponent::new(joint, ground, foot_left));
-    let trans = body.absolute.get::<FootRight>().translation;
-    let joint = SphericalJoint::new()
-        .local_anchor1(point!(trans.x, trans.y + 0.5, trans.z))
-        .local_anchor2(point!(0.0, 0.0, 0.0));
-    commands
-        .spawn()
-        .insert(JointBuilderComponent::new(joint, ground, foot_right));
 
-    let m = materials.add(StandardMaterial {
+    let mut entities: HashMap<BodyPartId, Entity> = HashMap::new();
+    let mut spawned = SpawnedBody(Vec::new());
+    for part in skeleton.parts.iter() {
+        let id = part.name;
+        let (center, size) = shape_box(skeleton, part, body.relative.part(id));
+        let (collider, mesh) = part_shape(skeleton, part, size);
+        let entity = spawn_body_part::<T>(commands, &body, part, id, center, collider);
+        if let Some(parent) = part.parent {
+            joint::<T>(
+                commands,
+                &body,
+                entities[&parent],
+                entity,
+                parent,
+                id,
+                part.joint,
+            );
+        }
+        let rest = to_ground.transform_point3(body.absolute.part(id).translation);
+        match part.anchor {
+            Some(Anchor::Rotation) => {
+                // Also pulled over the stance horizontally.
+                let joint = JointData::new(
+                    JointAxesMask::ANG_X | JointAxesMask::ANG_Y | JointAxesMask::ANG_Z,
+                )
+                .local_anchor1(to_rapier_point(rest))
+                .motor_position(JointAxis::X, 0.0, HIP_STIFFNESS, HIP_DAMPING)
+                .motor_position(JointAxis::Z, 0.0, HIP_STIFFNESS, HIP_DAMPING);
+                commands
+                    .spawn()
+                    .insert(T::default())
+                    .insert(GroundAnchor)
+                    .insert(JointBuilderComponent::new(joint, ground, entity));
+            }
+            Some(Anchor::Position) => {
+                let joint = SphericalJoint::new()
+                    .local_anchor1(to_rapier_point(rest))
+                    .local_anchor2(point!(0.0, 0.0, 0.0));
+                commands
+                    .spawn()
+                    .insert(T::default())
+                    .insert(GroundAnchor)
+                    .insert(FootPlant {
+                        rest,
+                        planted: body.absolute.part(id).translation,
+                        step: None,
+                    })
+                    .insert(JointBuilderComponent::new(joint, ground, entity));
+            }
+            None => {}
+        }
+        // ballon force
+        if let Some(coef) = part.balloon {
+            commands.entity(entity).insert(BallonForceCoef(coef));
+        }
+        if part.mesh {
+            spawned.0.push((entity, mesh));
+        }
+        entities.insert(id, entity);
+    }
+
+    (body, spawned)
+}
+
+fn body_material(
+    materials: &mut Assets<StandardMaterial>,
+    color: Color,
+) -> Handle<StandardMaterial> {
+    materials.add(StandardMaterial {
         base_color: color,
         roughness: 0.3,
         metallic: 0.0,
         reflectance: 0.1,
         ..Default::default()
-    });
-    insert_mesh::<T, Spine>(commands, meshes, m.clone(), &body, spine, torso_mesh::<T>);
-    insert_mesh::<T, Chest>(commands, meshes, m.clone(), &body, chest, torso_mesh::<T>);
-    insert_mesh::<T, Neck>(commands, meshes, m.clone(), &body, neck, neck_mesh::<T>);
-    insert_mesh::<T, Head>(commands, meshes, m.clone(), &body, head, head_mesh::<T>);
-    insert_mesh::<T, ForearmLeft>(
-        commands,
-        meshes,
-        m.clone(),
-        &body,
-        forearm_left,
-        arm_mesh::<T>,
-    );
-    insert_mesh::<T, ForearmRight>(
-        commands,
-        meshes,
-        m.clone(),
-        &body,
-        forearm_right,
-        arm_mesh::<T>,
-    );
-    insert_mesh::<T, HandLeft>(commands, meshes, m.clone(), &body, hand_left, arm_mesh::<T>);
-    insert_mesh::<T, HandRight>(
-        commands,
-        meshes,
-        m.clone(),
-        &body,
-        hand_right,
-        arm_mesh::<T>,
-    );
-    insert_mesh::<T, ShinLeft>(commands, meshes, m.clone(), &body, shin_left, leg_mesh::<T>);
-    insert_mesh::<T, ShinRight>(
-        commands,
-        meshes,
-        m.clone(),
-        &body,
-        shin_right,
-        leg_mesh::<T>,
-    );
-    insert_mesh::<T, FootLeft>(commands, meshes, m.clone(), &body, foot_left, leg_mesh::<T>);
-    insert_mesh::<T, FootRight>(
-        commands,
-        meshes,
-        m.clone(),
-        &body,
-        foot_right,
-        leg_mesh::<T>,
-    );
-
-    // ballon force
-    commands.entity(head).insert(BallonForceCoef(3.0));
-    commands.entity(hip).insert(BallonForceCoef(3.0));
-
-    // PlayerBody
-    commands.insert_resource(body);
-    commands.insert_resource(control);
+    })
 }
 
-fn insert_mesh<T: Player, P: BodyPart>(
+fn insert_meshes(
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     material: Handle<StandardMaterial>,
-    body: &PlayerBody<T>,
-    part: Entity,
-    builder: fn(&Transform) -> Mesh,
+    spawned: SpawnedBody,
 ) {
-    let transform = body.relative.get::<P>();
-    commands.entity(part).insert_bundle(PbrBundle {
-        mesh: meshes.add(builder(transform)),
-        material,
-        ..Default::default()
-    });
+    for (entity, mesh) in spawned.0 {
+        commands.entity(entity).insert_bundle(PbrBundle {
+            mesh: meshes.add(mesh),
+            material: material.clone(),
+            ..Default::default()
+        });
+    }
 }
 
 fn to_rapier_vec(vec: Vec3) -> Vector3<f32> {
@@ -205,35 +293,49 @@ fn to_rapier_point(vec: Vec3) -> Point<f32> {
     point!(vec.x, vec.y, vec.z)
 }
 
-fn spawn_body_part<T: Player, C: BodyPart>(
+fn spawn_body_part<T: Player>(
     commands: &mut Commands,
     body: &PlayerBody<T>,
-    collider_builder: fn(&Transform) -> ColliderBundle,
+    part: &PartDef,
+    id: BodyPartId,
+    center: Vec3,
+    shape: ColliderShape,
 ) -> Entity {
-    commands
-        .spawn()
+    let mut collider = collider_bundle::<T>(center, shape);
+    if let Some(density) = part.density {
+        collider.mass_properties = ColliderMassProps::Density(density).into();
+    }
+    let mut entity = commands.spawn();
+    id.insert_marker(&mut entity);
+    entity
         .insert(T::default())
-        .insert(C::default())
-        .insert(body.absolute.get::<C>().clone())
+        .insert(id)
+        .insert(body.absolute.part(id).clone())
         .insert_bundle(RigidBodyBundle {
-            position: to_rapier_vec(body.absolute.get::<C>().translation).into(),
+            // Turned with the player, so the joints work in the frame of the body.
+            position: to_isometry(body.absolute.part(id)).into(),
             ..Default::default()
         })
-        .insert_bundle(collider_builder(body.relative.get::<C>()))
+        .insert_bundle(collider)
         .insert(ColliderDebugRender::with_id(1))
         .insert(ColliderPositionSync::Discrete)
         .id()
 }
 
-fn joint<Parent: Component, Child: Component, T: Player>(
+fn joint<T: Player>(
     commands: &mut Commands,
     body: &PlayerBody<T>,
-    parent: Entity,
-    child: Entity,
+    parent_entity: Entity,
+    child_entity: Entity,
+    parent: BodyPartId,
+    child: BodyPartId,
+    JointDef {
+        stiffness,
+        damping,
+        driven,
+    }: JointDef,
 ) {
-    let stiffness = 1.5;
-    let damping = 1.5;
-    let child_translation = body.relative.get::<Child>().translation;
+    let child_translation = body.relative.part(child).translation;
     let joint = SphericalJoint::new()
         .local_anchor2(to_rapier_point(-child_translation))
         .motor_position(JointAxis::AngX, 0.0, stiffness, damping)
@@ -242,97 +344,79 @@ fn joint<Parent: Component, Child: Component, T: Player>(
         .motor_model(JointAxis::AngX, MotorModel::VelocityBased)
         .motor_model(JointAxis::AngY, MotorModel::VelocityBased)
         .motor_model(JointAxis::AngZ, MotorModel::VelocityBased);
-    let joint = JointBuilderComponent::new(joint, parent, child);
+    let joint = JointBuilderComponent::new(joint, parent_entity, child_entity);
     commands
         .spawn()
         .insert(T::default())
-        .insert(Joint::<Parent, Child>::default())
+        .insert(Joint {
+            parent,
+            child,
+            driven,
+        })
         .insert(JointMotorParams { stiffness, damping })
         .insert(joint);
 }
 
-fn small_collider<T: Player>(_transform: &Transform) -> ColliderBundle {
-    ColliderBundle {
-        mass_properties: ColliderMassProps::Density(50.0).into(),
-        ..collider_bundle::<T>(Vec3::ZERO, ColliderShape::cuboid(0.05, 0.05, 0.05))
+/// Center and size of the box of a part, relative to the part.
+/// Bones and capsules lie along the longest axis of their offset; balls fill a cube.
+fn shape_box(skeleton: &Skeleton, part: &PartDef, transform: &Transform) -> (Vec3, Vec3) {
+    let bone = |width: f32, thickness: f32| {
+        let (width, thickness) = (width * skeleton.ratio, thickness * skeleton.ratio);
+        let vec = transform.translation;
+        let length = vec.abs();
+        let size = if length.x >= length.y && length.x >= length.z {
+            Vec3::new(length.x, width, thickness)
+        } else if length.y >= length.z {
+            Vec3::new(width, length.y, thickness)
+        } else {
+            Vec3::new(width, thickness, length.z)
+        };
+        (-vec / 2.0, size)
+    };
+    match part.shape {
+        Shape::Cube { size } => (Vec3::ZERO, Vec3::from(size) * skeleton.ratio),
+        Shape::Bone { width, thickness } => bone(width, thickness),
+        Shape::Capsule { radius } => bone(radius * 2.0, radius * 2.0),
+        Shape::Ball { radius } => (
+            -transform.translation / 2.0,
+            Vec3::splat(radius * 2.0 * skeleton.ratio),
+        ),
     }
 }
 
-fn torso_collider<T: Player>(transform: &Transform) -> ColliderBundle {
-    let vec = transform.translation;
-    collider_bundle::<T>(
-        (-vec / 2.0).into(),
-        ColliderShape::cuboid(TORSO_WIDTH / 2.0, vec.y / 2.0, TORSO_THICKNESS / 2.0),
-    )
-}
-
-fn torso_mesh<T: Player>(transform: &Transform) -> Mesh {
-    let vec = transform.translation;
-    Mesh::from(bshape::Box::new(TORSO_WIDTH, vec.y, TORSO_THICKNESS))
-}
-
-fn neck_collider<T: Player>(transform: &Transform) -> ColliderBundle {
-    let vec = transform.translation;
-    collider_bundle::<T>(
-        -vec / 2.0,
-        ColliderShape::cuboid(NECK_RADIUS, vec.y / 2.0, NECK_RADIUS),
-    )
-}
-
-fn neck_mesh<T: Player>(transform: &Transform) -> Mesh {
-    let vec = transform.translation;
-    Mesh::from(bshape::Box::new(
-        NECK_RADIUS * 2.0,
-        vec.y,
-        NECK_RADIUS * 2.0,
-    ))
-}
-
-fn head_collider<T: Player>(transform: &Transform) -> ColliderBundle {
-    let vec = transform.translation;
-    collider_bundle::<T>(
-        -vec / 2.0,
-        ColliderShape::cuboid(HEAD_WIDTH / 2.0, vec.y / 2.0, HEAD_THICKNESS / 2.0),
-    )
-}
-
-fn head_mesh<T: Player>(transform: &Transform) -> Mesh {
-    let vec = transform.translation;
-    Mesh::from(bshape::Box::new(HEAD_WIDTH, vec.y, HEAD_THICKNESS))
-}
-
-fn arm_collider<T: Player>(transform: &Transform) -> ColliderBundle {
-    let vec = transform.translation;
-    collider_bundle::<T>(
-        (-vec / 2.0).into(),
-        ColliderShape::cuboid(vec.x.abs() / 2.0, ARM_RADIUS, ARM_RADIUS),
-    )
-}
-
-fn arm_mesh<T: Player>(transform: &Transform) -> Mesh {
-    let vec = transform.translation;
-    Mesh::from(bshape::Box::new(
-        vec.x.abs(),
-        ARM_RADIUS * 2.0,
-        ARM_RADIUS * 2.0,
-    ))
-}
-
-fn leg_collider<T: Player>(transform: &Transform) -> ColliderBundle {
-    let vec = transform.translation;
-    collider_bundle::<T>(
-        (-vec / 2.0).into(),
-        ColliderShape::cuboid(LEG_RADIUS, vec.y.abs() / 2.0, LEG_RADIUS),
-    )
-}
-
-fn leg_mesh<T: Player>(transform: &Transform) -> Mesh {
-    let vec = transform.translation;
-    Mesh::from(bshape::Box::new(
-        LEG_RADIUS * 2.0,
-        vec.y.abs(),
-        LEG_RADIUS * 2.0,
-    ))
+/// The collider and mesh filling the box of a part.
+fn part_shape(skeleton: &Skeleton, part: &PartDef, size: Vec3) -> (ColliderShape, Mesh) {
+    let half = size / 2.0;
+    match part.shape {
+        Shape::Ball { .. } => (
+            ColliderShape::ball(half.x),
+            Mesh::from(bshape::Icosphere {
+                radius: half.x,
+                subdivisions: 3,
+            }),
+        ),
+        Shape::Capsule { .. } => {
+            let radius = half.min_element();
+            // The segment runs along the longest axis.
+            let end = (half - Vec3::splat(radius)).max(Vec3::ZERO);
+            (
+                ColliderShape::capsule(to_rapier_point(-end), to_rapier_point(end), radius),
+                Mesh::from(RoundedBox::new(size, radius)),
+            )
+        }
+        _ if part.rounding > 0.0 => {
+            let radius = (part.rounding * skeleton.ratio).min(half.min_element());
+            let inner = half - Vec3::splat(radius);
+            (
+                ColliderShape::round_cuboid(inner.x, inner.y, inner.z, radius),
+                Mesh::from(RoundedBox::new(size, radius)),
+            )
+        }
+        _ => (
+            ColliderShape::cuboid(half.x, half.y, half.z),
+            Mesh::from(bshape::Box::new(size.x, size.y, size.z)),
+        ),
+    }
 }
 
 fn collider_bundle<T: Player>(position: Vec3, shape: ColliderShape) -> ColliderBundle {
@@ -345,6 +429,7 @@ fn collider_bundle<T: Player>(position: Vec3, shape: ColliderShape) -> ColliderB
                 collision_tag.into(),
                 (CollisionTag::ALL ^ collision_tag).into(),
             ),
+            active_events: ActiveEvents::CONTACT_EVENTS,
             ..Default::default()
         }
         .into(),
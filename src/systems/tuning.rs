@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+use crate::components::tuning::MotorTuning;
+
+/// `Y` switches to the next preset and `Shift+Y` reloads the motor tuning.
+pub fn tuning_input(input: Res<Input<KeyCode>>, mut tuning: ResMut<MotorTuning>) {
+    let shift = input.pressed(KeyCode::LShift) || input.pressed(KeyCode::RShift);
+    if input.just_pressed(KeyCode::Y) && shift {
+        let loaded = MotorTuning::load();
+        if loaded != *tuning {
+            *tuning = loaded;
+        }
+        info!("reloaded the motor tuning");
+    } else if input.just_pressed(KeyCode::Y) {
+        tuning.preset = tuning.preset.next();
+        info!("motor preset {:?}", tuning.preset);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
     adapters::share::navigator_share,
     components::{
-        networking::{ElapsedTime, PlayerName},
-        player::Player2,
+        hug::{HugEnded, HugScore, HugStats},
+        networking::PlayerName,
+        player::{Player1, Player2},
+        replay::ReplayRecorder,
         state::AppState,
         ui::{Alert, AlertTimer, Message, Messages},
     },
@@ -10,7 +12,10 @@ use crate::{
 
 use bevy::prelude::*;
 
-use super::networking::{RANDOM_BUTTON, ROOM_BUTTON, SHARE_BUTTON};
+use super::{
+    networking::{RANDOM_BUTTON, ROOM_BUTTON, SHARE_BUTTON},
+    replay::replay_url,
+};
 
 pub fn update_state_by_button(mut state: ResMut<State<AppState>>) {
     let mut random = RANDOM_BUTTON.lock();
@@ -61,9 +66,12 @@ pub fn remove_alert(mut timer: ResMut<AlertTimer>, time: Res<Time>) {
 
 pub fn share(
     state: Res<State<AppState>>,
+    name1: Res<PlayerName<Player1>>,
     name: Res<PlayerName<Player2>>,
     mut alert: ResMut<Alert>,
-    elapsed: Res<ElapsedTime>,
+    stats: Res<HugStats>,
+    score: Res<HugScore>,
+    recorder: Res<ReplayRecorder>,
     messages: Res<Messages>,
 ) {
     let mut button = SHARE_BUTTON.lock();
@@ -71,19 +79,38 @@ pub fn share(
         *button = false;
 
         let text;
+        let mut url = String::new();
         if *state.current() == AppState::Connected {
-            let seconds = elapsed.0.as_secs();
+            let seconds = stats.total.as_secs();
             let minutes = seconds / 60;
             let seconds = seconds % 60;
-            text = messages
-                .share_match
-                .to_string()
+            let mut share = messages.share_match.to_string();
+            if stats.count > 0 || score.seconds > 0.0 {
+                share += messages.share_best_score;
+            }
+            text = share
                 .replace("{name}", &name.0)
                 .replace("{minute}", &format!("{}", minutes))
-                .replace("{second}", &format!("{}", seconds));
+                .replace("{second}", &format!("{}", seconds))
+                .replace("{score}", &format!("{:.0}", score.best.max(score.score)));
+            url = replay_url(&recorder, &name1, &name);
         } else {
             text = messages.share.to_string();
         }
-        navigator_share(&text, "", &mut alert, &messages);
+        navigator_share(&text, &url, &mut alert, &messages);
+    }
+}
+
+/// Shows the score of a hug once it ends.
+pub fn alert_hug_score(
+    mut ended: EventReader<HugEnded>,
+    score: Res<HugScore>,
+    mut alert: ResMut<Alert>,
+    messages: Res<Messages>,
+) {
+    if ended.iter().count() > 0 {
+        alert.0 = messages
+            .hug_score
+            .replace("{score}", &format!("{:.0}", score.score));
     }
 }